@@ -1,52 +1,327 @@
 use anyhow::{Context, Result, bail};
+use governor::{RateLimiter, clock::DefaultClock, state::InMemoryState, state::NotKeyed};
 use reqwest::{
-    Client,
-    header::{ACCEPT, HeaderValue},
+    Client, StatusCode, Url,
+    header::{
+        ACCEPT, ETAG, HeaderMap, HeaderValue, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED,
+        RETRY_AFTER,
+    },
 };
-use serde::de::DeserializeOwned;
-use tracing::{Instrument, error, instrument};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, SystemTime},
+};
+use tracing::{Instrument, error, instrument, warn};
 
-use orcid_works_model::{OrcidWorkDetail, OrcidWorks};
+use orcid_works_model::{
+    OrcidAffiliations, OrcidBulkWorks, OrcidFundingDetail, OrcidFundings, OrcidId,
+    OrcidPeerReviewDetail, OrcidPeerReviews, OrcidPerson, OrcidWorkDetail, OrcidWorks,
+};
 
-const BASE: &str = "https://pub.orcid.org/v3.0";
+const PRODUCTION_BASE: &str = "https://pub.orcid.org/v3.0";
+const SANDBOX_BASE: &str = "https://pub.sandbox.orcid.org/v3.0";
 const JSON_ACCEPT: &str = "application/json";
 
+// Maximum number of put-codes accepted by the bulk works endpoint
+pub(crate) const BULK_MAX: usize = 100;
+
+// Per-request timeout; a timed-out request counts as a transient failure
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+// Upper bound for a single backoff or Retry-After wait
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(120);
+
+// Requests-per-second cap shared by all requests of a run
+pub(crate) type Limiter = RateLimiter<NotKeyed, InMemoryState, DefaultClock>;
+
+// Retry policy for transient failures (HTTP 429/5xx, timeouts, connection errors)
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub jitter: Duration,
+}
+
+impl RetryPolicy {
+    // Exponential backoff for the given (1-based) failed attempt, plus random jitter
+    fn backoff(&self, attempt: u32) -> Duration {
+        let exp = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(MAX_RETRY_DELAY);
+        let jitter_ms = u64::try_from(self.jitter.as_millis()).unwrap_or(u64::MAX);
+        exp + Duration::from_millis(fastrand::u64(0..=jitter_ms))
+    }
+}
+
+fn is_transient_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+// Timeouts and dropped connections, while sending or while reading the body
+fn is_transient_error(e: &reqwest::Error) -> bool {
+    e.is_timeout() || e.is_connect() || e.is_request() || e.is_body()
+}
+
+// Parse `Retry-After` as delay-seconds or an HTTP-date
+fn retry_after(headers: &HeaderMap, now: SystemTime) -> Option<Duration> {
+    let raw = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = raw.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = httpdate::parse_http_date(raw).ok()?;
+    Some(at.duration_since(now).unwrap_or_default())
+}
+
+// Wait before retrying a transient HTTP status: the server's `Retry-After`
+// (capped at MAX_RETRY_DELAY), else the policy's backoff
+fn status_retry_delay(
+    headers: &HeaderMap,
+    policy: &RetryPolicy,
+    attempt: u32,
+    now: SystemTime,
+) -> Duration {
+    retry_after(headers, now)
+        .map(|d| d.min(MAX_RETRY_DELAY))
+        .unwrap_or_else(|| policy.backoff(attempt))
+}
+
+// Parse `--api-base`: a preset name (`production`, `sandbox`) or an http(s) URL
+pub(crate) fn parse_api_base(s: &str) -> Result<String, String> {
+    let raw = match s.trim() {
+        "production" => PRODUCTION_BASE,
+        "sandbox" => SANDBOX_BASE,
+        other => other,
+    };
+
+    let url = Url::parse(raw).map_err(|e| format!("invalid API base URL {raw:?}: {e}"))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!(
+            "unsupported scheme {:?} in API base URL (expected http or https)",
+            url.scheme()
+        ));
+    }
+    if url.host_str().is_none() {
+        return Err(format!("API base URL {raw:?} has no host"));
+    }
+    if url.query().is_some() || url.fragment().is_some() {
+        return Err(format!(
+            "API base URL {raw:?} must not contain a query or fragment"
+        ));
+    }
+
+    Ok(url.as_str().trim_end_matches('/').to_string())
+}
+
+// Response validators kept from a previous GET for conditional requests
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Validators {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    #[serde(rename = "last-modified", skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+}
+
+impl Validators {
+    fn from_headers(headers: &HeaderMap) -> Self {
+        let get = |name| {
+            headers
+                .get(name)
+                .and_then(|v: &HeaderValue| v.to_str().ok())
+                .map(str::to_string)
+        };
+        Self {
+            etag: get(ETAG),
+            last_modified: get(LAST_MODIFIED),
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+}
+
+// Result of a conditional GET
+#[derive(Debug)]
+pub(crate) enum Conditional<T> {
+    Modified(T, Validators),
+    NotModified,
+}
+
+impl<T> Conditional<T> {
+    pub(crate) fn map<U>(self, f: impl FnOnce(T) -> U) -> Conditional<U> {
+        match self {
+            Self::Modified(value, validators) => Conditional::Modified(f(value), validators),
+            Self::NotModified => Conditional::NotModified,
+        }
+    }
+}
+
+// HTTP client bound to an API base; every endpoint URL is built from `base`
+#[derive(Debug, Clone)]
+pub(crate) struct ApiClient {
+    http: Client,
+    base: String,
+    retry: RetryPolicy,
+    limiter: Arc<Limiter>,
+    // HTTP requests sent, retries included; shared by clones
+    requests: Arc<AtomicU64>,
+}
+
+impl ApiClient {
+    pub(crate) fn base(&self) -> &str {
+        &self.base
+    }
+
+    pub(crate) fn request_count(&self) -> u64 {
+        self.requests.load(Ordering::Relaxed)
+    }
+
+    fn endpoint(&self, path: &str) -> String {
+        format!("{}/{path}", self.base)
+    }
+}
+
 // Build HTTP Client
-pub(crate) fn build_client(ua: &str) -> Result<Client> {
-    let client = Client::builder().user_agent(ua).build()?;
-    Ok(client)
+pub(crate) fn build_client(
+    ua: &str,
+    base: &str,
+    retry: RetryPolicy,
+    limiter: Arc<Limiter>,
+) -> Result<ApiClient> {
+    let http = Client::builder()
+        .user_agent(ua)
+        .timeout(REQUEST_TIMEOUT)
+        .build()?;
+    Ok(ApiClient {
+        http,
+        base: base.to_string(),
+        retry,
+        limiter,
+        requests: Arc::new(AtomicU64::new(0)),
+    })
+}
+
+// Response with its body read in full
+struct Fetched {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Vec<u8>,
+}
+
+// Send GET and read the body, retrying transient failures according to the
+// client's RetryPolicy. Every attempt waits for the rate limiter. A response
+// that keeps a transient status is returned as is once retries are exhausted.
+async fn send_with_retry(
+    client: &ApiClient,
+    url: &str,
+    validators: Option<&Validators>,
+) -> Result<Fetched> {
+    let policy = client.retry;
+    let mut attempt: u32 = 1;
+
+    loop {
+        let mut req = client
+            .http
+            .get(url)
+            .header(ACCEPT, HeaderValue::from_static(JSON_ACCEPT));
+        if let Some(v) = validators {
+            if let Some(etag) = &v.etag {
+                req = req.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &v.last_modified {
+                req = req.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+        client.limiter.until_ready().await;
+        client.requests.fetch_add(1, Ordering::Relaxed);
+
+        let result = match req.send().await {
+            Ok(res) => {
+                let status = res.status();
+                let headers = res.headers().clone();
+                res.bytes().await.map(|body| Fetched {
+                    status,
+                    headers,
+                    body: body.to_vec(),
+                })
+            }
+            Err(e) => Err(e),
+        };
+
+        let (delay, reason) = match &result {
+            Ok(res) if is_transient_status(res.status) => (
+                status_retry_delay(&res.headers, &policy, attempt, SystemTime::now()),
+                res.status.to_string(),
+            ),
+            Err(e) if is_transient_error(e) => (policy.backoff(attempt), e.to_string()),
+            _ => return result.with_context(|| format!("GET {url}")),
+        };
+
+        if attempt >= policy.max_attempts {
+            warn!(attempt, %url, %reason, "transient failure; retries exhausted");
+            return result.with_context(|| format!("GET {url}"));
+        }
+
+        warn!(
+            attempt,
+            max_attempts = policy.max_attempts,
+            delay_ms = u64::try_from(delay.as_millis()).unwrap_or(u64::MAX),
+            %url,
+            %reason,
+            "transient failure; retrying"
+        );
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
 }
 
 // Get JSON from URL
+async fn get_json<T>(client: &ApiClient, url: &str) -> Result<T>
+where
+    T: DeserializeOwned,
+{
+    match get_json_conditional(client, url, None).await? {
+        Conditional::Modified(parsed, _) => Ok(parsed),
+        Conditional::NotModified => bail!("unexpected 304 Not Modified while GET {url}"),
+    }
+}
+
+// Get JSON from URL unless it is unchanged since `validators` were issued
 #[instrument(name = "get_json", skip_all)]
-async fn get_json<T>(client: &Client, url: &str) -> Result<T>
+async fn get_json_conditional<T>(
+    client: &ApiClient,
+    url: &str,
+    validators: Option<&Validators>,
+) -> Result<Conditional<T>>
 where
     T: DeserializeOwned,
 {
-    let res = client
-        .get(url)
-        .header(ACCEPT, HeaderValue::from_static(JSON_ACCEPT))
-        .send()
-        .await
-        .with_context(|| format!("GET {url}"))?;
+    let res = send_with_retry(client, url, validators)
+        .in_current_span()
+        .await?;
+
+    if res.status == StatusCode::NOT_MODIFIED {
+        return Ok(Conditional::NotModified);
+    }
 
-    if res.error_for_status_ref().is_err() {
-        let status = res.status();
-        let body = res.text().await.unwrap_or_default();
+    if res.status.is_client_error() || res.status.is_server_error() {
+        let status = res.status;
+        let body = String::from_utf8_lossy(&res.body);
 
         error!(%status, %url, response_body = %body, "HTTP error");
         bail!("HTTP {status} while GET {url}: {body}");
     }
 
-    match res.json::<T>().await {
-        Ok(parsed) => Ok(parsed),
+    let validators = Validators::from_headers(&res.headers);
+    match serde_json::from_slice::<T>(&res.body) {
+        Ok(parsed) => Ok(Conditional::Modified(parsed, validators)),
 
         Err(e) => {
-            if e.is_decode() {
-                error!(%url, err = %e, "JSON parse failure");
-            } else {
-                error!(%url, err = %e, "response body read failure");
-            }
+            error!(%url, err = %e, "JSON parse failure");
             Err(e).with_context(|| format!("parse JSON from {url}"))
         }
     }
@@ -54,9 +329,13 @@ where
 
 // GET /{id}/works
 #[instrument(name = "fetch_works", skip_all)]
-pub async fn fetch_works(client: &reqwest::Client, id: &str) -> Result<OrcidWorks> {
-    let url = format!("{BASE}/{id}/works");
-    get_json::<OrcidWorks>(client, &url)
+pub async fn fetch_works(
+    client: &ApiClient,
+    id: &OrcidId,
+    validators: Option<&Validators>,
+) -> Result<Conditional<OrcidWorks>> {
+    let url = client.endpoint(&format!("{id}/works"));
+    get_json_conditional::<OrcidWorks>(client, &url, validators)
         .in_current_span()
         .await
         .with_context(|| format!("fetch work summaries for ORCID iD {id}"))
@@ -65,14 +344,266 @@ pub async fn fetch_works(client: &reqwest::Client, id: &str) -> Result<OrcidWork
 // GET /{id}/work/{putcode}
 #[instrument(name = "fetch_work_detail", skip_all)]
 pub async fn fetch_work_detail(
-    client: &reqwest::Client,
-    id: &str,
+    client: &ApiClient,
+    id: &OrcidId,
     putcode: u64,
 ) -> Result<OrcidWorkDetail> {
-    let url = format!("{BASE}/{id}/work/{putcode}");
+    let url = client.endpoint(&format!("{id}/work/{putcode}"));
 
     get_json::<OrcidWorkDetail>(client, &url)
         .in_current_span()
         .await
         .with_context(|| format!("fetch work detail of putcode {putcode}"))
 }
+
+// GET /{id}/works/{putcode,putcode,...}
+#[instrument(name = "fetch_work_details_bulk", skip_all)]
+pub async fn fetch_work_details_bulk(
+    client: &ApiClient,
+    id: &OrcidId,
+    putcodes: &[u64],
+) -> Result<OrcidBulkWorks> {
+    if putcodes.is_empty() || putcodes.len() > BULK_MAX {
+        bail!(
+            "bulk fetch takes 1..={BULK_MAX} putcodes, got {}",
+            putcodes.len()
+        );
+    }
+
+    let joined = putcodes
+        .iter()
+        .map(u64::to_string)
+        .collect::<Vec<_>>()
+        .join(",");
+    let url = client.endpoint(&format!("{id}/works/{joined}"));
+
+    get_json::<OrcidBulkWorks>(client, &url)
+        .in_current_span()
+        .await
+        .with_context(|| format!("bulk fetch work details of {} putcodes", putcodes.len()))
+}
+
+// GET /{id}/{section} for an affiliation section (employments, educations, ...)
+#[instrument(name = "fetch_affiliations", skip_all)]
+pub async fn fetch_affiliations(
+    client: &ApiClient,
+    id: &OrcidId,
+    section: &str,
+    validators: Option<&Validators>,
+) -> Result<Conditional<OrcidAffiliations>> {
+    let url = client.endpoint(&format!("{id}/{section}"));
+    get_json_conditional::<OrcidAffiliations>(client, &url, validators)
+        .in_current_span()
+        .await
+        .with_context(|| format!("fetch {section} for ORCID iD {id}"))
+}
+
+// GET /{id}/fundings
+#[instrument(name = "fetch_fundings", skip_all)]
+pub async fn fetch_fundings(
+    client: &ApiClient,
+    id: &OrcidId,
+    validators: Option<&Validators>,
+) -> Result<Conditional<OrcidFundings>> {
+    let url = client.endpoint(&format!("{id}/fundings"));
+    get_json_conditional::<OrcidFundings>(client, &url, validators)
+        .in_current_span()
+        .await
+        .with_context(|| format!("fetch funding summaries for ORCID iD {id}"))
+}
+
+// GET /{id}/funding/{putcode}
+#[instrument(name = "fetch_funding_detail", skip_all)]
+pub async fn fetch_funding_detail(
+    client: &ApiClient,
+    id: &OrcidId,
+    putcode: u64,
+) -> Result<OrcidFundingDetail> {
+    let url = client.endpoint(&format!("{id}/funding/{putcode}"));
+
+    get_json::<OrcidFundingDetail>(client, &url)
+        .in_current_span()
+        .await
+        .with_context(|| format!("fetch funding detail of putcode {putcode}"))
+}
+
+// GET /{id}/peer-reviews
+#[instrument(name = "fetch_peer_reviews", skip_all)]
+pub async fn fetch_peer_reviews(
+    client: &ApiClient,
+    id: &OrcidId,
+    validators: Option<&Validators>,
+) -> Result<Conditional<OrcidPeerReviews>> {
+    let url = client.endpoint(&format!("{id}/peer-reviews"));
+    get_json_conditional::<OrcidPeerReviews>(client, &url, validators)
+        .in_current_span()
+        .await
+        .with_context(|| format!("fetch peer-review summaries for ORCID iD {id}"))
+}
+
+// GET /{id}/peer-review/{putcode}
+#[instrument(name = "fetch_peer_review_detail", skip_all)]
+pub async fn fetch_peer_review_detail(
+    client: &ApiClient,
+    id: &OrcidId,
+    putcode: u64,
+) -> Result<OrcidPeerReviewDetail> {
+    let url = client.endpoint(&format!("{id}/peer-review/{putcode}"));
+
+    get_json::<OrcidPeerReviewDetail>(client, &url)
+        .in_current_span()
+        .await
+        .with_context(|| format!("fetch peer-review detail of putcode {putcode}"))
+}
+
+// GET /{id}/person
+#[instrument(name = "fetch_person", skip_all)]
+pub async fn fetch_person(client: &ApiClient, id: &OrcidId) -> Result<OrcidPerson> {
+    let url = client.endpoint(&format!("{id}/person"));
+    get_json::<OrcidPerson>(client, &url)
+        .in_current_span()
+        .await
+        .with_context(|| format!("fetch person data for ORCID iD {id}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(base_ms: u64, jitter_ms: u64) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 4,
+            base_delay: Duration::from_millis(base_ms),
+            jitter: Duration::from_millis(jitter_ms),
+        }
+    }
+
+    fn retry_after_header(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn backoff_doubles_per_attempt_up_to_the_cap() {
+        let p = policy(500, 0);
+        let delays: Vec<u64> = (1..=4).map(|a| p.backoff(a).as_millis() as u64).collect();
+        assert_eq!(delays, [500, 1000, 2000, 4000]);
+        assert_eq!(p.backoff(0), Duration::from_millis(500));
+        assert_eq!(p.backoff(20), MAX_RETRY_DELAY);
+        assert_eq!(p.backoff(u32::MAX), MAX_RETRY_DELAY);
+    }
+
+    #[test]
+    fn backoff_jitter_stays_in_range() {
+        let p = policy(100, 50);
+        for _ in 0..200 {
+            let d = p.backoff(1);
+            assert!(
+                (Duration::from_millis(100)..=Duration::from_millis(150)).contains(&d),
+                "{d:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn retry_after_delta_seconds() {
+        let now = SystemTime::now();
+        assert_eq!(
+            retry_after(&retry_after_header("120"), now),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            retry_after(&retry_after_header(" 0 "), now),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn retry_after_http_date() {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let later = httpdate::fmt_http_date(now + Duration::from_secs(30));
+        assert_eq!(
+            retry_after(&retry_after_header(&later), now),
+            Some(Duration::from_secs(30))
+        );
+        // A date in the past means "retry now"
+        let earlier = httpdate::fmt_http_date(now - Duration::from_secs(30));
+        assert_eq!(
+            retry_after(&retry_after_header(&earlier), now),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn retry_after_missing_or_invalid() {
+        let now = SystemTime::now();
+        assert_eq!(retry_after(&HeaderMap::new(), now), None);
+        assert_eq!(retry_after(&retry_after_header("soon"), now), None);
+        assert_eq!(retry_after(&retry_after_header("-5"), now), None);
+    }
+
+    #[test]
+    fn status_retry_delay_caps_retry_after_and_falls_back_to_backoff() {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let p = policy(500, 0);
+        assert_eq!(
+            status_retry_delay(&retry_after_header("3600"), &p, 1, now),
+            MAX_RETRY_DELAY
+        );
+        let in_an_hour = httpdate::fmt_http_date(now + Duration::from_secs(3600));
+        assert_eq!(
+            status_retry_delay(&retry_after_header(&in_an_hour), &p, 1, now),
+            MAX_RETRY_DELAY
+        );
+        assert_eq!(
+            status_retry_delay(&retry_after_header("7"), &p, 3, now),
+            Duration::from_secs(7)
+        );
+        assert_eq!(
+            status_retry_delay(&HeaderMap::new(), &p, 3, now),
+            Duration::from_millis(2000)
+        );
+    }
+
+    #[test]
+    fn api_base_presets() {
+        assert_eq!(parse_api_base("production").unwrap(), PRODUCTION_BASE);
+        assert_eq!(parse_api_base(" sandbox ").unwrap(), SANDBOX_BASE);
+    }
+
+    #[test]
+    fn api_base_custom_urls_lose_trailing_slashes() {
+        assert_eq!(
+            parse_api_base("http://localhost:8080/v3.0/").unwrap(),
+            "http://localhost:8080/v3.0"
+        );
+        assert_eq!(
+            parse_api_base("https://example.org/orcid/v3.0").unwrap(),
+            "https://example.org/orcid/v3.0"
+        );
+        assert_eq!(
+            parse_api_base("https://example.org/").unwrap(),
+            "https://example.org"
+        );
+    }
+
+    #[test]
+    fn api_base_rejects_other_schemes_and_bad_urls() {
+        for bad in [
+            "ftp://pub.orcid.org/v3.0",
+            "file:///tmp/v3.0",
+            "pub.orcid.org/v3.0",
+            "https://pub.orcid.org/v3.0?x=1",
+            "https://pub.orcid.org/v3.0#top",
+            "",
+        ] {
+            assert!(parse_api_base(bad).is_err(), "{bad:?} accepted");
+        }
+        assert!(
+            parse_api_base("ftp://pub.orcid.org")
+                .unwrap_err()
+                .contains("unsupported scheme")
+        );
+    }
+}
@@ -1,8 +1,11 @@
 use std::collections::{HashMap, HashSet};
 
-use orcid_works_model::{OrcidWorkDetail, OrcidWorks};
+use serde::Serialize;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+use orcid_works_model::{OrcidRecord, OrcidWorkSummary};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Diff {
     Added,
     Updated,
@@ -10,32 +13,46 @@ pub enum Diff {
     Deleted,
 }
 
-pub fn diff_putcodes(
-    older: &HashMap<u64, OrcidWorkDetail>,
-    newer: &OrcidWorks,
+impl Diff {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Added => "added",
+            Self::Updated => "updated",
+            Self::Kept => "kept",
+            Self::Deleted => "deleted",
+        }
+    }
+}
+
+// Diff stored records against the latest summaries of any record section
+pub fn diff_putcodes<'a, O, N>(
+    older: &HashMap<u64, O>,
+    newer: impl IntoIterator<Item = &'a N>,
     force_fetch: bool,
-) -> HashMap<u64, Diff> {
+) -> HashMap<u64, Diff>
+where
+    O: OrcidRecord,
+    N: OrcidRecord + 'a,
+{
     let mut diff = HashMap::new();
 
     let mut seen_old: HashSet<u64> = HashSet::new();
 
-    for g in &newer.group {
-        for s in &g.work_summary {
-            let pc = s.put_code;
-            let newt = s.last_modified_date.value;
+    for s in newer {
+        let pc = s.put_code();
+        let newt = s.last_modified();
 
-            match older.get(&pc) {
-                None => {
-                    diff.insert(pc, Diff::Added);
-                }
-                Some(t) => {
-                    seen_old.insert(pc);
-                    let oldt = t.summary.last_modified_date.value;
-                    if newt > oldt || force_fetch {
-                        diff.insert(pc, Diff::Updated);
-                    } else {
-                        diff.insert(pc, Diff::Kept);
-                    }
+        match older.get(&pc) {
+            None => {
+                diff.insert(pc, Diff::Added);
+            }
+            Some(t) => {
+                seen_old.insert(pc);
+                let oldt = t.last_modified();
+                if newt > oldt || force_fetch {
+                    diff.insert(pc, Diff::Updated);
+                } else {
+                    diff.insert(pc, Diff::Kept);
                 }
             }
         }
@@ -50,6 +67,28 @@ pub fn diff_putcodes(
     diff
 }
 
+// Summary fields that disagree between the latest work summary and the
+// summary stored in the work detail, e.g. after a hand edit
+pub fn verify_work_summary(
+    latest: &OrcidWorkSummary,
+    stored: &OrcidWorkSummary,
+) -> Vec<&'static str> {
+    let mut reasons = Vec::new();
+    if latest.title != stored.title {
+        reasons.push("title");
+    }
+    if latest.r#type != stored.r#type {
+        reasons.push("type");
+    }
+    if latest.external_ids != stored.external_ids {
+        reasons.push("external-ids");
+    }
+    if latest.publication_date != stored.publication_date {
+        reasons.push("publication-date");
+    }
+    reasons
+}
+
 pub fn added_putcodes(diff: &HashMap<u64, Diff>) -> Vec<u64> {
     diff.iter()
         .filter_map(|(&pc, &d)| (d == Diff::Added).then_some(pc))
@@ -73,3 +112,134 @@ pub fn deleted_putcodes(diff: &HashMap<u64, Diff>) -> Vec<u64> {
         .filter_map(|(&pc, &d)| (d == Diff::Deleted).then_some(pc))
         .collect()
 }
+
+// One top-level field that differs between two versions of a record;
+// `None` when the field is absent on that side
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub old: Option<serde_json::Value>,
+    pub new: Option<serde_json::Value>,
+}
+
+// Fields that change on every edit and carry no content of their own
+const IGNORED_FIELDS: [&str; 1] = ["last-modified-date"];
+
+// Structural comparison of two versions of a record (e.g. two `OrcidWorkDetail`s),
+// field by field in their on-disk JSON form: title, contributors, external-ids,
+// publication-date, journal-title, ...
+pub fn diff_fields<T: Serialize>(old: &T, new: &T) -> Vec<FieldChange> {
+    let as_object = |r: &T| match serde_json::to_value(r) {
+        Ok(serde_json::Value::Object(map)) => map,
+        _ => serde_json::Map::new(),
+    };
+    let (old, new) = (as_object(old), as_object(new));
+
+    let mut fields: Vec<&String> = old.keys().chain(new.keys()).collect();
+    fields.sort();
+    fields.dedup();
+
+    fields
+        .into_iter()
+        .filter(|f| !IGNORED_FIELDS.contains(&f.as_str()))
+        .filter(|f| old.get(*f) != new.get(*f))
+        .map(|f| FieldChange {
+            field: f.clone(),
+            old: old.get(f).cloned(),
+            new: new.get(f).cloned(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use orcid_works_model::{OrcidWorkDetail, OrcidWorkDetailFile};
+    use serde_json::json;
+
+    const FIXTURE: &str = include_str!("../tests/fixtures/works.json");
+
+    fn fixture() -> Vec<OrcidWorkDetail> {
+        serde_json::from_str::<OrcidWorkDetailFile>(FIXTURE)
+            .expect("fixture parses")
+            .records
+    }
+
+    #[test]
+    fn identical_records_have_no_field_changes() {
+        let work = &fixture()[0];
+        assert!(diff_fields(work, &work.clone()).is_empty());
+    }
+
+    #[test]
+    fn last_modified_date_is_ignored() {
+        let old = &fixture()[0];
+        let mut new = old.clone();
+        new.summary.last_modified_date.value += 86_400_000;
+        assert!(diff_fields(old, &new).is_empty());
+    }
+
+    #[test]
+    fn changed_added_and_removed_fields_in_key_order() {
+        let old = &fixture()[0];
+        let mut new = old.clone();
+        new.summary.last_modified_date.value += 1;
+        new.summary.title.title.value = "New title".to_string();
+        new.short_description = None;
+        new.summary.r#type = "preprint".to_string();
+
+        let changes = diff_fields(old, &new);
+        let fields: Vec<&str> = changes.iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(fields, ["short-description", "title", "type"]);
+
+        let removed = &changes[0];
+        assert!(removed.old.is_some());
+        assert_eq!(removed.new, None);
+
+        let title = &changes[1];
+        assert_eq!(title.new.as_ref().unwrap()["title"]["value"], "New title");
+        assert_eq!(changes[2].old, Some(json!("journal-article")));
+        assert_eq!(changes[2].new, Some(json!("preprint")));
+    }
+
+    #[test]
+    fn non_object_values_compare_as_empty() {
+        assert!(diff_fields(&json!(1), &json!(2)).is_empty());
+        let changes = diff_fields(&json!({ "a": 1 }), &json!({ "a": 1, "b": [] }));
+        assert_eq!(
+            changes,
+            [FieldChange {
+                field: "b".to_string(),
+                old: None,
+                new: Some(json!([])),
+            }]
+        );
+    }
+
+    #[test]
+    fn put_codes_are_classified_by_timestamp() {
+        let works = fixture();
+        let older: HashMap<u64, OrcidWorkDetail> = works[..3]
+            .iter()
+            .map(|w| (w.put_code(), w.clone()))
+            .collect();
+        // 1001 kept, 1002 updated, 1003 deleted, 1004 added
+        let mut newer = vec![works[0].clone(), works[1].clone(), works[3].clone()];
+        newer[1].summary.last_modified_date.value += 1;
+
+        let diff = diff_putcodes(&older, &newer, false);
+        assert_eq!(diff[&1001], Diff::Kept);
+        assert_eq!(diff[&1002], Diff::Updated);
+        assert_eq!(diff[&1003], Diff::Deleted);
+        assert_eq!(diff[&1004], Diff::Added);
+
+        // An older timestamp than the stored one is not an update
+        newer[1].summary.last_modified_date.value -= 2;
+        assert_eq!(diff_putcodes(&older, &newer, false)[&1002], Diff::Kept);
+
+        let forced = diff_putcodes(&older, &newer, true);
+        assert_eq!(forced[&1001], Diff::Updated);
+        assert_eq!(forced[&1002], Diff::Updated);
+        assert_eq!(forced[&1004], Diff::Added);
+    }
+}
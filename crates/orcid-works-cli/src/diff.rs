@@ -0,0 +1,316 @@
+use anyhow::{Context, Result};
+use clap::{Args, ValueEnum};
+use serde::Serialize;
+use std::{collections::HashMap, io::Write, path::Path, path::PathBuf};
+
+use orcid_works_model::{OrcidRecord, OrcidWorkDetail, OrcidWorkDetailFile};
+
+use crate::compare::{Diff, FieldChange, diff_fields, diff_putcodes};
+use crate::io::read_required_records;
+use crate::report::Change;
+
+// Output format of the `diff` subcommand
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub(crate) enum DiffFormat {
+    Text,
+    Json,
+    Markdown,
+}
+
+// `diff` subcommand: compare two work-details files without network access
+#[derive(Args)]
+pub(crate) struct DiffArgs {
+    #[arg(help = "Older work-details JSON (e.g. a snapshot from git history)")]
+    old: PathBuf,
+
+    #[arg(help = "Newer work-details JSON")]
+    new: PathBuf,
+
+    #[arg(
+        long = "format",
+        value_enum,
+        default_value_t = DiffFormat::Text,
+        help = "Output format"
+    )]
+    format: DiffFormat,
+}
+
+// A work present in both files whose content or timestamp changed
+#[derive(Serialize)]
+struct Modified {
+    #[serde(flatten)]
+    change: Change,
+    // Empty when only `last-modified-date` advanced
+    fields: Vec<FieldChange>,
+}
+
+#[derive(Serialize)]
+struct FileDiff {
+    added: Vec<Change>,
+    removed: Vec<Change>,
+    modified: Vec<Modified>,
+}
+
+fn read_file(path: &Path, which: &str) -> Result<OrcidWorkDetailFile> {
+    read_required_records(path)
+        .with_context(|| format!("open the {which} work-details JSON from {}", path.display()))
+}
+
+// Classify the works of two files with the sync's `Diff` categories; works
+// kept by timestamp but with different content count as modified
+fn diff_files(old: OrcidWorkDetailFile, new: OrcidWorkDetailFile) -> FileDiff {
+    let old_map: HashMap<u64, OrcidWorkDetail> =
+        old.records.into_iter().map(|d| (d.put_code(), d)).collect();
+    let new_map: HashMap<u64, OrcidWorkDetail> =
+        new.records.into_iter().map(|d| (d.put_code(), d)).collect();
+    let diff_map = diff_putcodes(&old_map, new_map.values(), false);
+
+    let mut putcodes: Vec<u64> = diff_map.keys().copied().collect();
+    putcodes.sort_unstable();
+
+    let mut out = FileDiff {
+        added: vec![],
+        removed: vec![],
+        modified: vec![],
+    };
+    for pc in putcodes {
+        let (old, new) = (old_map.get(&pc), new_map.get(&pc));
+        match diff_map[&pc] {
+            Diff::Added => out.added.extend(Change::new(Diff::Added, old, new)),
+            Diff::Deleted => out.removed.extend(Change::new(Diff::Deleted, old, new)),
+            Diff::Updated | Diff::Kept => {
+                let (Some(o), Some(n)) = (old, new) else {
+                    continue;
+                };
+                let fields = diff_fields(o, n);
+                if diff_map[&pc] == Diff::Kept && fields.is_empty() {
+                    continue;
+                }
+                if let Some(change) = Change::new(Diff::Updated, old, new) {
+                    out.modified.push(Modified { change, fields });
+                }
+            }
+        }
+    }
+    out
+}
+
+// Print added, removed and modified works between two work-details files
+pub(crate) fn run_diff(args: &DiffArgs) -> Result<()> {
+    let old = read_file(&args.old, "old")?;
+    let new = read_file(&args.new, "new")?;
+    let diff = diff_files(old, new);
+
+    let mut stdout = std::io::stdout().lock();
+    match args.format {
+        DiffFormat::Text => write_text(&mut stdout, &diff)?,
+        DiffFormat::Json => {
+            serde_json::to_writer_pretty(&mut stdout, &diff).context("serialize diff")?;
+            writeln!(stdout)?;
+        }
+        DiffFormat::Markdown => write_markdown(&mut stdout, &diff)?,
+    }
+    Ok(())
+}
+
+fn write_text(w: &mut impl Write, diff: &FileDiff) -> Result<()> {
+    let line = |w: &mut dyn Write, label: &str, c: &Change| {
+        writeln!(
+            w,
+            "{label:<8} {:>12}  {}",
+            c.put_code,
+            c.title.as_deref().unwrap_or("-")
+        )
+    };
+    for c in &diff.added {
+        line(w, "added", c)?;
+    }
+    for c in &diff.removed {
+        line(w, "removed", c)?;
+    }
+    for m in &diff.modified {
+        line(w, "modified", &m.change)?;
+        for f in &m.fields {
+            writeln!(
+                w,
+                "    {}: {} -> {}",
+                f.field,
+                compact(f.old.as_ref()),
+                compact(f.new.as_ref())
+            )?;
+        }
+    }
+    writeln!(
+        w,
+        "{} added, {} removed, {} modified",
+        diff.added.len(),
+        diff.removed.len(),
+        diff.modified.len()
+    )?;
+    Ok(())
+}
+
+fn write_markdown(w: &mut impl Write, diff: &FileDiff) -> Result<()> {
+    for (heading, changes) in [("Added", &diff.added), ("Removed", &diff.removed)] {
+        writeln!(w, "## {heading} ({})\n", changes.len())?;
+        if changes.is_empty() {
+            continue;
+        }
+        writeln!(w, "| Put-code | Title | DOI |")?;
+        writeln!(w, "| ---: | :--- | :--- |")?;
+        for c in changes {
+            writeln!(
+                w,
+                "| {} | {} | {} |",
+                c.put_code,
+                cell(c.title.as_deref().unwrap_or("")),
+                cell(c.doi.as_deref().unwrap_or(""))
+            )?;
+        }
+        writeln!(w)?;
+    }
+
+    writeln!(w, "## Modified ({})\n", diff.modified.len())?;
+    for m in &diff.modified {
+        writeln!(
+            w,
+            "### {} {}\n",
+            m.change.put_code,
+            cell(m.change.title.as_deref().unwrap_or(""))
+        )?;
+        if m.fields.is_empty() {
+            writeln!(w, "Only `last-modified-date` changed.\n")?;
+            continue;
+        }
+        writeln!(w, "| Field | Old | New |")?;
+        writeln!(w, "| :--- | :--- | :--- |")?;
+        for f in &m.fields {
+            writeln!(
+                w,
+                "| `{}` | {} | {} |",
+                f.field,
+                code_cell(f.old.as_ref()),
+                code_cell(f.new.as_ref())
+            )?;
+        }
+        writeln!(w)?;
+    }
+    Ok(())
+}
+
+// Field value on one line; `(none)` when absent
+fn compact(value: Option<&serde_json::Value>) -> String {
+    value.map_or_else(|| "(none)".to_string(), serde_json::Value::to_string)
+}
+
+// Markdown table cells must not contain pipes or line breaks
+fn cell(s: &str) -> String {
+    s.replace('|', "\\|").replace('\n', " ")
+}
+
+// Value as an inline code span. The fence is one backtick longer than the
+// longest backtick run inside, and padded with spaces when the value starts
+// or ends with a backtick, so backticks in titles cannot close it early.
+fn code_cell(value: Option<&serde_json::Value>) -> String {
+    let Some(v) = value else {
+        return "*(none)*".to_string();
+    };
+    let text = cell(&v.to_string());
+    let longest = text
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or_default();
+    let fence = "`".repeat(longest + 1);
+    let pad = if text.starts_with('`') || text.ends_with('`') {
+        " "
+    } else {
+        ""
+    };
+    format!("{fence}{pad}{text}{pad}{fence}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const FIXTURE: &str = include_str!("../tests/fixtures/works.json");
+
+    fn fixture() -> OrcidWorkDetailFile {
+        serde_json::from_str(FIXTURE).expect("fixture parses")
+    }
+
+    fn markdown(diff: &FileDiff) -> String {
+        let mut out = Vec::new();
+        write_markdown(&mut out, diff).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn code_cells_survive_backticks() {
+        assert_eq!(code_cell(None), "*(none)*");
+        assert_eq!(code_cell(Some(&json!(2021))), "`2021`");
+        assert_eq!(code_cell(Some(&json!("a `b` c"))), "``\"a `b` c\"``");
+        assert_eq!(code_cell(Some(&json!("x``y"))), "```\"x``y\"```");
+        assert_eq!(code_cell(Some(&json!(["`"]))), "``[\"`\"]``");
+    }
+
+    #[test]
+    fn code_cells_escape_pipes_and_line_breaks() {
+        assert_eq!(code_cell(Some(&json!("a|b"))), "`\"a\\|b\"`");
+        let cell = code_cell(Some(&json!({ "k": "line\nbreak" })));
+        assert!(!cell.contains('\n'), "{cell}");
+    }
+
+    #[test]
+    fn title_with_backticks_renders_one_code_span_per_cell() {
+        let old = fixture();
+        let mut new = old.clone();
+        new.records[0].summary.title.title.value = "Using `grep` in ``pottery``".to_string();
+        new.records[0].summary.last_modified_date.value += 1;
+
+        let md = markdown(&diff_files(old, new));
+        let row = md
+            .lines()
+            .find(|l| l.starts_with("| `title` |"))
+            .expect("title row");
+        // Field, old and new cells: four pipes, none inside the code spans
+        assert_eq!(row.matches(" | ").count() + 2, 4, "{row}");
+        assert!(
+            row.ends_with(
+                "| ```{\"subtitle\":{\"value\":\"A review\"},\"title\":{\"value\":\"Using `grep` in ``pottery``\"}}``` |"
+            ),
+            "{row}"
+        );
+    }
+
+    #[test]
+    fn timestamp_only_changes_list_no_fields() {
+        let old = fixture();
+        let mut new = old.clone();
+        new.records[1].summary.last_modified_date.value += 1;
+
+        let diff = diff_files(old, new);
+        assert!(diff.added.is_empty() && diff.removed.is_empty());
+        assert_eq!(diff.modified.len(), 1);
+        assert!(diff.modified[0].fields.is_empty());
+        assert!(markdown(&diff).contains("Only `last-modified-date` changed."));
+    }
+
+    #[test]
+    fn added_removed_and_kept_works() {
+        let old = fixture();
+        let mut new = old.clone();
+        let removed = new.records.remove(0);
+        let mut added = new.records[0].clone();
+        added.summary.put_code = 9999;
+
+        new.records.push(added);
+        let diff = diff_files(old, new);
+        let pcs = |cs: &[Change]| cs.iter().map(|c| c.put_code).collect::<Vec<_>>();
+        assert_eq!(pcs(&diff.added), [9999]);
+        assert_eq!(pcs(&diff.removed), [removed.put_code()]);
+        assert!(diff.modified.is_empty());
+    }
+}
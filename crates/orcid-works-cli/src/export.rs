@@ -0,0 +1,533 @@
+use anyhow::{Context, Result, bail};
+use clap::{ArgAction, Args, ValueEnum};
+use std::{collections::HashMap, io::Write, path::PathBuf};
+use unicode_normalization::UnicodeNormalization;
+
+use orcid_works_model::{Contributor, OrcidRecord, OrcidWorkDetail, OrcidWorkDetailFile};
+
+use crate::io::{read_required_records, write_text};
+
+mod bibtex;
+mod cff;
+mod csl;
+mod jsonld;
+mod ris;
+mod table;
+
+pub(crate) use jsonld::to_json_ld;
+use table::{Column, DEFAULT_COLUMNS, RowMode};
+
+// Formats of the `export` subcommand
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub(crate) enum ExportFormat {
+    Bibtex,
+    CslJson,
+    Ris,
+    JsonLd,
+    Cff,
+    Csv,
+    Tsv,
+}
+
+// `export` subcommand: convert a work-details file for reference tools
+#[derive(Args)]
+pub(crate) struct ExportArgs {
+    #[arg(help = "Work-details JSON written by a sync")]
+    input: PathBuf,
+
+    #[arg(short = 'f', long = "format", value_enum, help = "Export format")]
+    format: ExportFormat,
+
+    #[arg(
+        long = "put-code",
+        action = ArgAction::Append,
+        help = "Only export this work; repeat for several (in the given order). For cff the first one is the cited software"
+    )]
+    put_code: Vec<u64>,
+
+    #[arg(
+        long = "software-version",
+        help = "`version` of the cited software (cff only)"
+    )]
+    software_version: Option<String>,
+
+    #[arg(
+        long = "any-type",
+        help = "Cite a work that is neither software nor a data set, as software (cff only)"
+    )]
+    any_type: bool,
+
+    #[arg(
+        long = "columns",
+        value_enum,
+        value_delimiter = ',',
+        help = "Columns of the csv/tsv export, comma-separated and in order [default: put-code,title,type,year,journal,doi,contributors,source,visibility]"
+    )]
+    columns: Vec<Column>,
+
+    #[arg(
+        long = "rows",
+        value_enum,
+        help = "One csv/tsv row per work, or per contributor of each work [default: work]"
+    )]
+    rows: Option<RowMode>,
+
+    #[arg(
+        short = 'o',
+        long = "out",
+        help = "Output file (parent dirs are created); printed to stdout if omitted"
+    )]
+    out: Option<PathBuf>,
+}
+
+pub(crate) fn run_export(args: &ExportArgs) -> Result<()> {
+    let file: OrcidWorkDetailFile = read_required_records(&args.input)
+        .with_context(|| format!("open the work-details JSON from {}", args.input.display()))?;
+
+    if args.format == ExportFormat::Cff && args.put_code.is_empty() {
+        bail!("--format cff needs the cited work; select it with --put-code");
+    }
+    if args.software_version.is_some() && args.format != ExportFormat::Cff {
+        bail!("--software-version only applies to --format cff");
+    }
+    if args.any_type && args.format != ExportFormat::Cff {
+        bail!("--any-type only applies to --format cff");
+    }
+    let table = matches!(args.format, ExportFormat::Csv | ExportFormat::Tsv);
+    if !table && (!args.columns.is_empty() || args.rows.is_some()) {
+        bail!("--columns and --rows only apply to --format csv/tsv");
+    }
+    let columns = if args.columns.is_empty() {
+        &DEFAULT_COLUMNS[..]
+    } else {
+        &args.columns
+    };
+    let rows = args.rows.unwrap_or(RowMode::Work);
+    let keys = CitationKeys::new(&file.records);
+    // Before the selection, so that any work can name the profile owner
+    let owner = owner_name(&file.records).map(str::to_string);
+    let records = select(file.records, &args.put_code)?;
+
+    let text = match args.format {
+        ExportFormat::Bibtex => bibtex::to_bibtex(&records, &keys),
+        ExportFormat::CslJson => csl::to_csl_json(&records, &keys)?,
+        ExportFormat::Ris => ris::to_ris(&records, &keys),
+        ExportFormat::JsonLd => to_json_ld(&records)?,
+        ExportFormat::Cff => cff::to_cff(
+            &records,
+            owner.as_deref(),
+            args.software_version.as_deref(),
+            args.any_type,
+        )?,
+        ExportFormat::Csv => table::to_table(&records, columns, rows, b',')?,
+        ExportFormat::Tsv => table::to_table(&records, columns, rows, b'\t')?,
+    };
+
+    match &args.out {
+        Some(path) => {
+            write_text(path, &text).with_context(|| format!("write export to {}", path.display()))
+        }
+        None => {
+            let mut stdout = std::io::stdout().lock();
+            stdout.write_all(text.as_bytes())?;
+            Ok(stdout.flush()?)
+        }
+    }
+}
+
+// Works with the given put-codes in that order, or all works if none are given
+fn select(records: Vec<OrcidWorkDetail>, put_codes: &[u64]) -> Result<Vec<OrcidWorkDetail>> {
+    if put_codes.is_empty() {
+        return Ok(records);
+    }
+    let mut by_code: HashMap<u64, OrcidWorkDetail> =
+        records.into_iter().map(|w| (w.put_code(), w)).collect();
+    let mut selected = Vec::with_capacity(put_codes.len());
+    for pc in put_codes {
+        match by_code.remove(pc) {
+            Some(work) => selected.push(work),
+            // Repeated put-codes are only exported once
+            None if selected
+                .iter()
+                .any(|w: &OrcidWorkDetail| w.put_code() == *pc) => {}
+            None => bail!("put-code {pc} not found in the work-details file"),
+        }
+    }
+    Ok(selected)
+}
+
+// Publication year, if recorded
+pub(crate) fn year(work: &OrcidWorkDetail) -> Option<&str> {
+    let date = work.summary.publication_date.as_ref()?;
+    Some(date.year.value.as_str()).filter(|y| !y.is_empty())
+}
+
+// Publication month 1-12, if recorded
+pub(crate) fn month(work: &OrcidWorkDetail) -> Option<u32> {
+    let date = work.summary.publication_date.as_ref()?;
+    let month: u32 = date.month.as_ref()?.value.parse().ok()?;
+    (1..=12).contains(&month).then_some(month)
+}
+
+// Publication day 1-31, if recorded
+pub(crate) fn day(work: &OrcidWorkDetail) -> Option<u32> {
+    let date = work.summary.publication_date.as_ref()?;
+    let day: u32 = date.day.as_ref()?.value.parse().ok()?;
+    (1..=31).contains(&day).then_some(day)
+}
+
+fn external_id<'a>(work: &'a OrcidWorkDetail, id_type: &str) -> Option<&'a str> {
+    work.summary.external_ids.find(id_type)
+}
+
+pub(crate) fn journal(work: &OrcidWorkDetail) -> Option<&str> {
+    work.journal_title.as_ref().map(|j| j.value.as_str())
+}
+
+pub(crate) fn url(work: &OrcidWorkDetail) -> Option<&str> {
+    work.url.as_ref().map(|u| u.value.as_str())
+}
+
+// Public ORCID host, for records that do not name their registry
+pub(crate) const ORCID_HOST: &str = "orcid.org";
+
+// Registry the work was fetched from (`orcid.org`, `sandbox.orcid.org`, ...),
+// as stamped by the API on the work's source
+pub(crate) fn orcid_host(work: &OrcidWorkDetail) -> &str {
+    let source = &work.summary.source;
+    source
+        .source_orcid
+        .iter()
+        .chain(&source.source_client_id)
+        .find_map(|s| s.host.as_deref())
+        .filter(|host| !host.is_empty())
+        .unwrap_or(ORCID_HOST)
+}
+
+// ORCID URI of a work, e.g. https://orcid.org/0000-0002-1825-0097/work/123
+pub(crate) fn work_uri(work: &OrcidWorkDetail) -> String {
+    format!(
+        "https://{}/{}",
+        orcid_host(work),
+        work.summary.path.trim_start_matches('/')
+    )
+}
+
+// iD of the profile a work belongs to, from its `/<iD>/work/<put-code>` path
+pub(crate) fn owner_id(work: &OrcidWorkDetail) -> &str {
+    work.summary
+        .path
+        .trim_start_matches('/')
+        .split('/')
+        .next()
+        .unwrap_or_default()
+}
+
+// Name of the profile owner: their credit name on their own works, else the
+// source name of a work they added themselves
+pub(crate) fn owner_name(works: &[OrcidWorkDetail]) -> Option<&str> {
+    let credited = works.iter().find_map(|work| {
+        let owner = owner_id(work);
+        contributors(work, "author")
+            .filter(|c| {
+                c.contributor_orcid
+                    .as_ref()
+                    .and_then(|o| o.path.as_deref())
+                    .is_some_and(|p| p == owner)
+            })
+            .find_map(credit_name)
+    });
+    credited.or_else(|| {
+        works.iter().find_map(|work| {
+            let source = &work.summary.source;
+            source
+                .source_orcid
+                .as_ref()
+                .and_then(|o| o.path.as_deref())
+                .filter(|p| *p == owner_id(work))?;
+            source
+                .source_name
+                .as_ref()
+                .map(|n| n.value.trim())
+                .filter(|n| !n.is_empty())
+        })
+    })
+}
+
+// Resolver link for a DOI; DOIs recorded as URLs are kept as they are
+pub(crate) fn doi_url(doi: &str) -> String {
+    if doi.starts_with("http://") || doi.starts_with("https://") {
+        doi.to_string()
+    } else {
+        format!("https://doi.org/{doi}")
+    }
+}
+
+// Contributors with the given role (`author`, `editor`, ...), in file order.
+// A contributor without a role counts as an author.
+pub(crate) fn contributors<'a>(
+    work: &'a OrcidWorkDetail,
+    role: &'a str,
+) -> impl Iterator<Item = &'a Contributor> {
+    work.contributors
+        .iter()
+        .flat_map(|c| c.contributor.iter().flatten())
+        .filter(move |c| {
+            let r = c
+                .contributor_attributes
+                .as_ref()
+                .and_then(|a| a.contributor_role.as_deref())
+                .unwrap_or("author");
+            r.replace('_', "-").eq_ignore_ascii_case(role)
+        })
+}
+
+pub(crate) fn credit_name(c: &Contributor) -> Option<&str> {
+    c.credit_name
+        .as_ref()
+        .map(|n| n.value.trim())
+        .filter(|n| !n.is_empty())
+}
+
+// Personal name split into given and family parts
+struct PersonName {
+    given: Option<String>,
+    family: String,
+}
+
+// Lower-case particles that belong to the family name ("van der Berg")
+const NAME_PARTICLES: [&str; 14] = [
+    "van", "von", "der", "den", "de", "del", "della", "da", "dos", "du", "di", "la", "le", "ter",
+];
+
+// Split an ORCID `credit-name`: "Family, Given" is taken literally, otherwise
+// the last word plus any preceding particles is the family name.
+fn split_name(name: &str) -> PersonName {
+    if let Some((family, given)) = name.split_once(',') {
+        let given = given.trim();
+        return PersonName {
+            given: (!given.is_empty()).then(|| given.to_string()),
+            family: family.trim().to_string(),
+        };
+    }
+
+    let words: Vec<&str> = name.split_whitespace().collect();
+    let mut start = words.len().saturating_sub(1);
+    while start > 1 && NAME_PARTICLES.contains(&words[start - 1]) {
+        start -= 1;
+    }
+    PersonName {
+        given: (start > 0).then(|| words[..start].join(" ")),
+        family: words[start..].join(" "),
+    }
+}
+
+// Lower-case ASCII letters and digits only, with accents stripped ("Müller" -> "muller")
+fn ascii_key(s: &str) -> String {
+    s.nfd()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+// Title words skipped when building citation keys
+const KEY_STOPWORDS: [&str; 10] = [
+    "a", "an", "the", "on", "of", "in", "for", "and", "to", "with",
+];
+
+// `{first author family}{year}{first significant title word}`, e.g. `carberry2001psychoceramics`;
+// works without an author fall back to `orcid{put-code}`
+fn base_key(work: &OrcidWorkDetail) -> String {
+    let author = contributors(work, "author")
+        .find_map(credit_name)
+        .map(|n| ascii_key(&split_name(n).family))
+        .filter(|k| !k.is_empty());
+    let Some(author) = author else {
+        return format!("orcid{}", work.put_code());
+    };
+    let word = work
+        .summary
+        .title
+        .title
+        .value
+        .split_whitespace()
+        .map(ascii_key)
+        .find(|w| !w.is_empty() && !KEY_STOPWORDS.contains(&w.as_str()))
+        .unwrap_or_default();
+    format!("{author}{}{word}", year(work).unwrap_or_default())
+}
+
+// Citation keys by put-code: BibTeX keys, CSL-JSON and RIS ids. Built from
+// the whole work-details file, so a work's key does not depend on which works
+// are exported. Colliding keys get a suffix (`b`, `c`, ...) in put-code order;
+// ORCID hands out increasing put-codes, so a new colliding work never changes
+// the key of an older one.
+pub(super) struct CitationKeys(HashMap<u64, String>);
+
+impl CitationKeys {
+    pub(super) fn new(works: &[OrcidWorkDetail]) -> Self {
+        let mut sorted: Vec<&OrcidWorkDetail> = works.iter().collect();
+        sorted.sort_by_key(|w| w.put_code());
+
+        let mut used: HashMap<String, u32> = HashMap::new();
+        let keys = sorted
+            .into_iter()
+            .map(|work| {
+                let base = base_key(work);
+                let seen = used.entry(base.clone()).or_default();
+                let key = match *seen {
+                    0 => base,
+                    n => format!("{base}{}", suffix(n)),
+                };
+                *seen += 1;
+                (work.put_code(), key)
+            })
+            .collect();
+        Self(keys)
+    }
+
+    // Key of a work of the file the keys were built from
+    pub(super) fn get(&self, work: &OrcidWorkDetail) -> String {
+        self.0
+            .get(&work.put_code())
+            .cloned()
+            .unwrap_or_else(|| base_key(work))
+    }
+}
+
+// Disambiguation suffix for the n-th repeat of a key: b, c, ..., z, ba, bb, ...
+fn suffix(mut n: u32) -> String {
+    let mut letters = Vec::new();
+    loop {
+        letters.push(char::from(b'a' + (n % 26) as u8));
+        n /= 26;
+        if n == 0 {
+            break;
+        }
+    }
+    letters.iter().rev().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = include_str!("../tests/fixtures/works.json");
+
+    fn fixture() -> Vec<OrcidWorkDetail> {
+        serde_json::from_str::<OrcidWorkDetailFile>(FIXTURE)
+            .expect("fixture parses")
+            .records
+    }
+
+    fn name(s: &str) -> (Option<String>, String) {
+        let n = split_name(s);
+        (n.given, n.family)
+    }
+
+    fn given(given: &str, family: &str) -> (Option<String>, String) {
+        (Some(given.to_string()), family.to_string())
+    }
+
+    #[test]
+    fn split_name_family_comma_given() {
+        assert_eq!(name("Carberry, Josiah"), given("Josiah", "Carberry"));
+        assert_eq!(name(" Müller ,  Jürgen "), given("Jürgen", "Müller"));
+        assert_eq!(name("Carberry,"), (None, "Carberry".to_string()));
+    }
+
+    #[test]
+    fn split_name_last_word_with_particles() {
+        assert_eq!(name("Josiah Carberry"), given("Josiah", "Carberry"));
+        assert_eq!(
+            name("Grace  Brewster Hopper"),
+            given("Grace Brewster", "Hopper")
+        );
+        assert_eq!(
+            name("Ludwig van Beethoven"),
+            given("Ludwig", "van Beethoven")
+        );
+        assert_eq!(name("Jean de la Fontaine"), given("Jean", "de la Fontaine"));
+        // A particle is not taken when nothing would be left as given name
+        assert_eq!(name("van Gogh"), given("van", "Gogh"));
+        assert_eq!(name("Plato"), (None, "Plato".to_string()));
+        assert_eq!(name(""), (None, String::new()));
+    }
+
+    #[test]
+    fn base_keys_use_author_year_and_title_word() {
+        let works = fixture();
+        let keys = CitationKeys::new(&works);
+        let all: Vec<String> = works.iter().map(|w| keys.get(w)).collect();
+        assert_eq!(all[0], "carberry2021psychoceramics");
+        assert_eq!(all[3], "orcid1004");
+        assert_eq!(suffix(1), "b");
+        assert_eq!(suffix(25), "z");
+        assert_eq!(suffix(26), "ba");
+    }
+
+    // Three works sharing author, year and first title word
+    fn colliding() -> Vec<OrcidWorkDetail> {
+        let first = fixture().remove(0);
+        [1001, 1005, 1003]
+            .into_iter()
+            .map(|pc| {
+                let mut w = first.clone();
+                w.summary.put_code = pc;
+                w
+            })
+            .collect()
+    }
+
+    #[test]
+    fn colliding_keys_get_suffixes_in_put_code_order() {
+        let works = colliding();
+        let keys = CitationKeys::new(&works);
+        let by_pc = |pc: u64| keys.get(works.iter().find(|w| w.put_code() == pc).unwrap());
+        assert_eq!(by_pc(1001), "carberry2021psychoceramics");
+        assert_eq!(by_pc(1003), "carberry2021psychoceramicsb");
+        assert_eq!(by_pc(1005), "carberry2021psychoceramicsc");
+
+        // Same keys whatever the file order
+        let mut reversed = works.clone();
+        reversed.reverse();
+        let again = CitationKeys::new(&reversed);
+        for w in &works {
+            assert_eq!(again.get(w), keys.get(w));
+        }
+    }
+
+    #[test]
+    fn keys_do_not_depend_on_the_selection() {
+        let works = colliding();
+        let keys = CitationKeys::new(&works);
+        let selected = select(works.clone(), &[1005]).unwrap();
+        assert_eq!(keys.get(&selected[0]), "carberry2021psychoceramicsc");
+        let selected = select(works, &[1005, 1001]).unwrap();
+        let got: Vec<String> = selected.iter().map(|w| keys.get(w)).collect();
+        assert_eq!(
+            got,
+            ["carberry2021psychoceramicsc", "carberry2021psychoceramics"]
+        );
+    }
+
+    #[test]
+    fn work_uris_follow_the_source_registry() {
+        let mut works = fixture();
+        assert_eq!(
+            work_uri(&works[0]),
+            "https://orcid.org/0000-0002-1825-0097/work/1001"
+        );
+
+        let source = works[0].summary.source.source_orcid.as_mut().unwrap();
+        source.host = Some("sandbox.orcid.org".to_string());
+        assert_eq!(orcid_host(&works[0]), "sandbox.orcid.org");
+        assert_eq!(
+            work_uri(&works[0]),
+            "https://sandbox.orcid.org/0000-0002-1825-0097/work/1001"
+        );
+
+        works[0].summary.source.source_orcid = None;
+        assert_eq!(orcid_host(&works[0]), ORCID_HOST);
+    }
+}
@@ -0,0 +1,243 @@
+use std::fmt::Write;
+
+use orcid_works_model::{OrcidRecord, OrcidWorkDetail};
+
+use super::{
+    CitationKeys, contributors, credit_name, external_id, journal, month, split_name, url, year,
+};
+
+const MONTHS: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+
+// BibTeX entry type for an ORCID work type, and the field holding `journal-title`
+fn entry_type(work_type: &str) -> (&'static str, &'static str) {
+    match work_type {
+        "journal-article" | "journal-issue" | "magazine-article" | "newspaper-article"
+        | "review" => ("article", "journal"),
+        "conference-paper" | "conference-abstract" | "conference-poster" => {
+            ("inproceedings", "booktitle")
+        }
+        "conference-proceedings" => ("proceedings", "series"),
+        "book" | "edited-book" => ("book", "series"),
+        "book-chapter" | "encyclopedia-entry" | "dictionary-entry" => ("incollection", "booktitle"),
+        "dissertation" | "dissertation-thesis" => ("phdthesis", "school"),
+        "report" | "working-paper" => ("techreport", "institution"),
+        "manual" => ("manual", "organization"),
+        _ => ("misc", "howpublished"),
+    }
+}
+
+// Escape LaTeX specials in a braced field value
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => out.push_str("\\textbackslash{}"),
+            '{' | '}' | '&' | '%' | '$' | '#' | '_' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '~' => out.push_str("\\textasciitilde{}"),
+            '^' => out.push_str("\\textasciicircum{}"),
+            '\n' | '\r' => out.push(' '),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+// URL and DOI fields are verbatim; only drop characters that would unbalance the entry
+fn verbatim(s: &str) -> String {
+    s.chars().filter(|c| !matches!(c, '{' | '}')).collect()
+}
+
+// BibTeX name list: "Family, Given and Family, Given"
+fn names(work: &OrcidWorkDetail, role: &str) -> Option<String> {
+    let names: Vec<String> = contributors(work, role)
+        .filter_map(credit_name)
+        .map(|n| {
+            let n = split_name(n);
+            match n.given {
+                Some(given) => format!("{}, {given}", escape(&n.family)),
+                None => format!("{{{}}}", escape(&n.family)),
+            }
+        })
+        .collect();
+    (!names.is_empty()).then(|| names.join(" and "))
+}
+
+// An embedded citation the work's source already supplied as BibTeX
+fn embedded(work: &OrcidWorkDetail) -> Option<&str> {
+    let citation = work.citation.as_ref()?;
+    let value = citation.citation_value.trim();
+    (citation.citation_type.eq_ignore_ascii_case("bibtex") && value.starts_with('@'))
+        .then_some(value)
+}
+
+// `@type{key, ...}` (or `@type(key, ...)`) with its key replaced by `key`, so
+// embedded entries cannot collide with generated ones; an entry without a key
+// gets one. `None` if the entry has no field list to attach the key to.
+fn rekey(entry: &str, key: &str) -> Option<String> {
+    let open = entry.find(['{', '('])?;
+    if !entry[1..open]
+        .trim()
+        .chars()
+        .all(|c| c.is_ascii_alphabetic())
+    {
+        return None;
+    }
+    let body = &entry[open + 1..];
+    let comma = body.find(',')?;
+    let old = body[..comma].trim();
+    let rest = if old.contains(['=', '{', '}', '"']) {
+        // No key: the first field starts right after the brace
+        body.trim_start()
+    } else {
+        body[comma + 1..].trim_start()
+    };
+    Some(format!("{}{key},\n  {rest}", &entry[..=open]))
+}
+
+pub(super) fn to_bibtex(works: &[OrcidWorkDetail], keys: &CitationKeys) -> String {
+    let mut out = String::new();
+
+    for work in works {
+        let key = keys.get(work);
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        if let Some(entry) = embedded(work).and_then(|e| rekey(e, &key)) {
+            out.push_str(&entry);
+            out.push('\n');
+            continue;
+        }
+
+        let (kind, container) = entry_type(&work.summary.r#type);
+        let mut fields: Vec<(&str, String)> = Vec::new();
+        if let Some(authors) = names(work, "author") {
+            fields.push(("author", authors));
+        }
+        if let Some(editors) = names(work, "editor") {
+            fields.push(("editor", editors));
+        }
+        let title = &work.summary.title;
+        fields.push(("title", escape(&title.title.value)));
+        if let Some(subtitle) = &title.subtitle {
+            fields.push(("subtitle", escape(&subtitle.value)));
+        }
+        if let Some(journal) = journal(work) {
+            fields.push((container, escape(journal)));
+        }
+        if let Some(year) = year(work) {
+            fields.push(("year", escape(year)));
+        }
+        if let Some(doi) = work.doi() {
+            fields.push(("doi", verbatim(doi)));
+        }
+        if let Some(url) = url(work) {
+            fields.push(("url", verbatim(url)));
+        }
+        for (id_type, field) in [("isbn", "isbn"), ("issn", "issn")] {
+            if let Some(value) = external_id(work, id_type) {
+                fields.push((field, escape(value)));
+            }
+        }
+        if let Some(language) = &work.language_code {
+            fields.push(("language", escape(language)));
+        }
+        if let Some(abstract_) = &work.short_description {
+            fields.push(("abstract", escape(abstract_)));
+        }
+
+        let _ = writeln!(out, "@{kind}{{{key},");
+        for (name, value) in &fields {
+            let _ = writeln!(out, "  {name} = {{{value}}},");
+        }
+        if let Some(m) = month(work) {
+            // Month macros are written unbraced
+            let _ = writeln!(out, "  month = {},", MONTHS[m as usize - 1]);
+        }
+        out.push_str("}\n");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use orcid_works_model::{Citation, OrcidWorkDetailFile};
+
+    const FIXTURE: &str = include_str!("../../tests/fixtures/works.json");
+
+    fn fixture() -> Vec<OrcidWorkDetail> {
+        serde_json::from_str::<OrcidWorkDetailFile>(FIXTURE)
+            .expect("fixture parses")
+            .records
+    }
+
+    // Entry keys of a .bib file, in order
+    fn entry_keys(bib: &str) -> Vec<&str> {
+        bib.lines()
+            .filter(|l| l.starts_with('@'))
+            .map(|l| {
+                let open = l.find(['{', '(']).unwrap();
+                l[open + 1..].split(',').next().unwrap().trim()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rekey_replaces_or_adds_the_key() {
+        assert_eq!(
+            rekey("@article{Carberry_2021, title={Pots}}", "k").as_deref(),
+            Some("@article{k,\n  title={Pots}}")
+        );
+        assert_eq!(
+            rekey("@Book(old,\n  title = \"Pots\")", "k").as_deref(),
+            Some("@Book(k,\n  title = \"Pots\")")
+        );
+        assert_eq!(
+            rekey("@misc{title = {Pots}, year = 2020}", "k").as_deref(),
+            Some("@misc{k,\n  title = {Pots}, year = 2020}")
+        );
+        assert_eq!(rekey("@misc{onlykey}", "k"), None);
+        assert_eq!(rekey("@not an entry{x, y}", "k"), None);
+    }
+
+    #[test]
+    fn embedded_entries_take_the_generated_key() {
+        let mut works = fixture();
+        // The source's key equals the generated key of another work
+        works[1].citation = Some(Citation {
+            citation_type: "bibtex".to_string(),
+            citation_value: "@book{carberry2021psychoceramics,\n  title = {Edited}\n}".to_string(),
+        });
+        let keys = CitationKeys::new(&works);
+        let bib = to_bibtex(&works, &keys);
+
+        let got = entry_keys(&bib);
+        let expected: Vec<String> = works.iter().map(|w| keys.get(w)).collect();
+        assert_eq!(got, expected);
+        let mut unique = got.clone();
+        unique.sort_unstable();
+        unique.dedup();
+        assert_eq!(unique.len(), got.len(), "duplicate keys in {bib}");
+        assert!(bib.contains("  title = {Edited}"), "{bib}");
+    }
+
+    #[test]
+    fn embedded_entries_without_fields_are_regenerated() {
+        let mut works = fixture();
+        works[0].citation = Some(Citation {
+            citation_type: "BIBTEX".to_string(),
+            citation_value: "@article{broken}".to_string(),
+        });
+        let bib = to_bibtex(&works[..1], &CitationKeys::new(&works));
+        assert!(
+            bib.starts_with("@article{carberry2021psychoceramics,\n"),
+            "{bib}"
+        );
+        assert!(!bib.contains("broken"));
+    }
+}
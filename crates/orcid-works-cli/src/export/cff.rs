@@ -0,0 +1,556 @@
+use anyhow::{Result, bail};
+use std::fmt::Write;
+use tracing::{info, warn};
+
+use orcid_works_model::{Contributor, OrcidId, OrcidRecord, OrcidWorkDetail};
+
+use super::{
+    ORCID_HOST, contributors, credit_name, day, journal, month, orcid_host, owner_id, split_name,
+    url, year,
+};
+
+const CFF_VERSION: &str = "1.2.0";
+
+// Hosts whose URLs are written as `repository-code` rather than `url`
+const CODE_HOSTS: [&str; 5] = [
+    "github.com",
+    "gitlab.com",
+    "bitbucket.org",
+    "codeberg.org",
+    "sr.ht",
+];
+
+struct Author {
+    family_names: String,
+    given_names: Option<String>,
+    orcid: Option<String>,
+}
+
+struct Identifier {
+    kind: &'static str,
+    value: String,
+    description: Option<String>,
+}
+
+// Further selected works, listed under `references`
+struct Reference {
+    kind: &'static str,
+    title: String,
+    authors: Vec<Author>,
+    doi: Option<String>,
+    year: Option<u32>,
+    journal: Option<String>,
+    url: Option<String>,
+}
+
+// CITATION.cff, Citation File Format 1.2.0
+struct Cff {
+    cff_version: &'static str,
+    message: &'static str,
+    kind: &'static str,
+    title: String,
+    authors: Vec<Author>,
+    version: Option<String>,
+    date_released: Option<String>,
+    doi: Option<String>,
+    identifiers: Vec<Identifier>,
+    repository_code: Option<String>,
+    url: Option<String>,
+    abstract_: Option<String>,
+    references: Vec<Reference>,
+}
+
+// CFF reference type (schema 1.2.0 enum) for an ORCID work type
+fn reference_type(work_type: &str) -> &'static str {
+    match work_type {
+        "journal-article" | "journal-issue" | "review" => "article",
+        "preprint" => "unpublished",
+        "magazine-article" => "magazine-article",
+        "newspaper-article" => "newspaper-article",
+        "book" | "book-chapter" => "book",
+        "edited-book" => "edited-work",
+        "conference-paper" | "conference-abstract" | "conference-poster" => "conference-paper",
+        "conference-proceedings" => "proceedings",
+        "dissertation" | "dissertation-thesis" => "thesis",
+        "report" | "working-paper" => "report",
+        "data-set" => "data",
+        "software" => "software",
+        "patent" => "patent",
+        "encyclopedia-entry" => "encyclopedia",
+        "dictionary-entry" => "dictionary",
+        "website" | "online-resource" => "website",
+        "lecture-speech" => "slides",
+        "manual" => "manual",
+        "standards-and-policy" => "standard",
+        _ => "generic",
+    }
+}
+
+// Canonical ORCID URI of an iD; iDs failing the checksum are dropped, and so
+// are iDs of other registries (sandbox), which the CFF schema rejects
+fn orcid_uri(raw: &str, host: &str) -> Option<String> {
+    if host != ORCID_HOST {
+        warn!(
+            orcid = raw,
+            host, "ORCID iD outside orcid.org; omitted from CFF"
+        );
+        return None;
+    }
+    match raw.parse::<OrcidId>() {
+        Ok(id) => Some(id.uri()),
+        Err(err) => {
+            warn!(orcid = raw, err = %err, "invalid ORCID iD; omitted from CFF");
+            None
+        }
+    }
+}
+
+fn author_orcid(work: &OrcidWorkDetail, c: &Contributor) -> Option<String> {
+    let orcid = c.contributor_orcid.as_ref()?;
+    let raw = orcid.path.as_deref().or(orcid.uri.as_deref())?;
+    orcid_uri(raw, orcid.host.as_deref().unwrap_or(orcid_host(work)))
+}
+
+// The work's authors; a work without any is credited to the profile owner
+fn authors(work: &OrcidWorkDetail, owner: Option<&str>) -> Vec<Author> {
+    let authors: Vec<Author> = contributors(work, "author")
+        .filter_map(|c| {
+            let name = split_name(credit_name(c)?);
+            Some(Author {
+                family_names: name.family,
+                given_names: name.given,
+                orcid: author_orcid(work, c),
+            })
+        })
+        .collect();
+    if !authors.is_empty() {
+        return authors;
+    }
+    let Some(owner) = owner else {
+        return authors;
+    };
+    info!(
+        put_code = work.put_code(),
+        author = owner,
+        "work has no authors; citing the profile owner"
+    );
+    let name = split_name(owner);
+    vec![Author {
+        family_names: name.family,
+        given_names: name.given,
+        orcid: orcid_uri(owner_id(work), orcid_host(work)),
+    }]
+}
+
+// `date-released` needs a full date
+fn date_released(work: &OrcidWorkDetail) -> Option<String> {
+    Some(format!(
+        "{}-{:02}-{:02}",
+        year(work)?,
+        month(work)?,
+        day(work)?
+    ))
+}
+
+// All external ids of the work as CFF identifiers
+fn identifiers(work: &OrcidWorkDetail) -> Vec<Identifier> {
+    let ids = work.summary.external_ids.external_id.iter().flatten();
+    ids.filter(|e| e.external_id_relationship == "self")
+        .map(|e| {
+            let t = e.external_id_type.to_ascii_lowercase();
+            let (kind, description) = match t.as_str() {
+                "doi" => ("doi", None),
+                "swh" => ("swh", None),
+                "uri" | "url" => ("url", None),
+                _ => ("other", Some(t.clone())),
+            };
+            Identifier {
+                kind,
+                value: e.external_id_value.clone(),
+                description,
+            }
+        })
+        .collect()
+}
+
+fn is_code_host(url: &str) -> bool {
+    let host = url
+        .split_once("://")
+        .map_or(url, |(_, rest)| rest)
+        .split('/')
+        .next()
+        .unwrap_or_default();
+    let host = host.strip_prefix("www.").unwrap_or(host);
+    CODE_HOSTS.contains(&host)
+}
+
+fn to_reference(work: &OrcidWorkDetail, owner: Option<&str>) -> Reference {
+    Reference {
+        kind: reference_type(&work.summary.r#type),
+        title: work.summary.title.title.value.clone(),
+        authors: authors(work, owner),
+        doi: work.doi().map(str::to_string),
+        year: year(work).and_then(|y| y.parse().ok()),
+        journal: journal(work).map(str::to_string),
+        url: url(work).map(str::to_string),
+    }
+}
+
+// DOI syntax accepted by the CFF schema: `10.<registrant>/<suffix>`
+fn valid_doi(doi: &str) -> bool {
+    let Some((prefix, suffix)) = doi.split_once('/') else {
+        return false;
+    };
+    let Some(registrant) = prefix.strip_prefix("10.") else {
+        return false;
+    };
+    let (code, sub) = registrant.split_once('.').unwrap_or((registrant, "1"));
+    (4..=9).contains(&code.len())
+        && code.bytes().all(|b| b.is_ascii_digit())
+        && !sub.is_empty()
+        && sub.bytes().all(|b| b.is_ascii_digit())
+        && !suffix.is_empty()
+        && suffix
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || ":/_;-.()[]\\".contains(c))
+}
+
+fn valid_url(url: &str) -> bool {
+    ["https://", "http://", "ftp://", "sftp://"]
+        .iter()
+        .any(|scheme| {
+            url.strip_prefix(scheme)
+                .is_some_and(|rest| !rest.is_empty())
+        })
+}
+
+// `YYYY-MM-DD`
+fn valid_date(date: &str) -> bool {
+    let parts: Vec<&str> = date.split('-').collect();
+    parts.len() == 3
+        && [4, 2, 2]
+            .iter()
+            .zip(&parts)
+            .all(|(len, p)| p.len() == *len && p.bytes().all(|b| b.is_ascii_digit()))
+}
+
+fn check_authors(errors: &mut Vec<String>, at: &str, authors: &[Author]) {
+    if authors.is_empty() {
+        errors.push(format!("{at}authors: at least one author is required"));
+    }
+    for (i, a) in authors.iter().enumerate() {
+        if a.family_names.is_empty() {
+            errors.push(format!("{at}authors[{i}].family-names: must not be empty"));
+        }
+        if let Some(orcid) = &a.orcid
+            && orcid
+                .strip_prefix("https://orcid.org/")
+                .is_none_or(|id| id.parse::<OrcidId>().is_err())
+        {
+            errors.push(format!(
+                "{at}authors[{i}].orcid: {orcid} is not an ORCID URI"
+            ));
+        }
+    }
+}
+
+// Constraints of the CFF 1.2.0 schema on the keys written here: required keys,
+// non-empty author lists and the DOI, ORCID, URL and date formats
+fn validate(cff: &Cff) -> Vec<String> {
+    let mut errors = vec![];
+    if cff.title.trim().is_empty() {
+        errors.push("title: must not be empty".to_string());
+    }
+    check_authors(&mut errors, "", &cff.authors);
+    if cff.version.as_deref().is_some_and(|v| v.trim().is_empty()) {
+        errors.push("version: must not be empty".to_string());
+    }
+    if let Some(date) = &cff.date_released
+        && !valid_date(date)
+    {
+        errors.push(format!("date-released: {date} is not YYYY-MM-DD"));
+    }
+    if let Some(doi) = &cff.doi
+        && !valid_doi(doi)
+    {
+        errors.push(format!("doi: {doi} is not a DOI (10.xxxx/...)"));
+    }
+    for (i, id) in cff.identifiers.iter().enumerate() {
+        let ok = match id.kind {
+            "doi" => valid_doi(&id.value),
+            "url" => valid_url(&id.value),
+            "swh" => id.value.starts_with("swh:1:"),
+            _ => !id.value.is_empty(),
+        };
+        if !ok {
+            errors.push(format!(
+                "identifiers[{i}]: {} is not a valid {} identifier",
+                id.value, id.kind
+            ));
+        }
+    }
+    for (key, url) in [("repository-code", &cff.repository_code), ("url", &cff.url)] {
+        if let Some(url) = url
+            && !valid_url(url)
+        {
+            errors.push(format!("{key}: {url} is not an http(s)/ftp URL"));
+        }
+    }
+    for (i, r) in cff.references.iter().enumerate() {
+        let at = format!("references[{i}].");
+        if r.title.trim().is_empty() {
+            errors.push(format!("{at}title: must not be empty"));
+        }
+        check_authors(&mut errors, &at, &r.authors);
+        if let Some(doi) = &r.doi
+            && !valid_doi(doi)
+        {
+            errors.push(format!("{at}doi: {doi} is not a DOI (10.xxxx/...)"));
+        }
+        if let Some(url) = &r.url
+            && !valid_url(url)
+        {
+            errors.push(format!("{at}url: {url} is not an http(s)/ftp URL"));
+        }
+    }
+    errors
+}
+
+// YAML double-quoted scalar
+fn quoted(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{:04x}", u32::from(c));
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+// `key: "value"` at the given indentation; nothing when the value is absent
+fn field(out: &mut String, indent: &str, key: &str, value: Option<&str>) {
+    if let Some(value) = value {
+        let _ = writeln!(out, "{indent}{key}: {}", quoted(value));
+    }
+}
+
+fn write_authors(out: &mut String, indent: &str, authors: &[Author]) {
+    let _ = writeln!(out, "{indent}authors:");
+    for a in authors {
+        let _ = writeln!(out, "{indent}  - family-names: {}", quoted(&a.family_names));
+        let inner = format!("{indent}    ");
+        field(out, &inner, "given-names", a.given_names.as_deref());
+        field(out, &inner, "orcid", a.orcid.as_deref());
+    }
+}
+
+fn to_yaml(cff: &Cff) -> String {
+    let mut out = String::new();
+    field(&mut out, "", "cff-version", Some(cff.cff_version));
+    field(&mut out, "", "message", Some(cff.message));
+    let _ = writeln!(out, "type: {}", cff.kind);
+    field(&mut out, "", "title", Some(&cff.title));
+    write_authors(&mut out, "", &cff.authors);
+    field(&mut out, "", "version", cff.version.as_deref());
+    field(&mut out, "", "date-released", cff.date_released.as_deref());
+    field(&mut out, "", "doi", cff.doi.as_deref());
+    if !cff.identifiers.is_empty() {
+        out.push_str("identifiers:\n");
+        for id in &cff.identifiers {
+            let _ = writeln!(out, "  - type: {}", id.kind);
+            field(&mut out, "    ", "value", Some(&id.value));
+            field(&mut out, "    ", "description", id.description.as_deref());
+        }
+    }
+    field(
+        &mut out,
+        "",
+        "repository-code",
+        cff.repository_code.as_deref(),
+    );
+    field(&mut out, "", "url", cff.url.as_deref());
+    field(&mut out, "", "abstract", cff.abstract_.as_deref());
+    if !cff.references.is_empty() {
+        out.push_str("references:\n");
+        for r in &cff.references {
+            let _ = writeln!(out, "  - type: {}", r.kind);
+            field(&mut out, "    ", "title", Some(&r.title));
+            write_authors(&mut out, "    ", &r.authors);
+            field(&mut out, "    ", "doi", r.doi.as_deref());
+            if let Some(year) = r.year {
+                let _ = writeln!(out, "    year: {year}");
+            }
+            field(&mut out, "    ", "journal", r.journal.as_deref());
+            field(&mut out, "    ", "url", r.url.as_deref());
+        }
+    }
+    out
+}
+
+// CITATION.cff for the first work; the other works become `references`.
+// Works without authors are credited to `owner`, the profile owner's name.
+// The cited work must be software or a data set, the only CFF types, unless
+// `any_type` is set; it is then described as software.
+pub(super) fn to_cff(
+    works: &[OrcidWorkDetail],
+    owner: Option<&str>,
+    version: Option<&str>,
+    any_type: bool,
+) -> Result<String> {
+    let Some((main, rest)) = works.split_first() else {
+        bail!("CFF export needs at least one work");
+    };
+    let kind = match main.summary.r#type.as_str() {
+        "software" => "software",
+        "data-set" => "dataset",
+        other if any_type => {
+            warn!(
+                put_code = main.put_code(),
+                r#type = other,
+                "cited work is neither software nor a data set; written as software"
+            );
+            "software"
+        }
+        other => bail!(
+            "put-code {} is a {other}, but CITATION.cff cites software or a data set; pass --any-type to cite it as software",
+            main.put_code()
+        ),
+    };
+    let url = url(main).map(str::to_string);
+    let (repository_code, url) = match url {
+        Some(u) if is_code_host(&u) => (Some(u), None),
+        u => (None, u),
+    };
+    let cff = Cff {
+        cff_version: CFF_VERSION,
+        message: if kind == "dataset" {
+            "If you use this dataset, please cite it using the metadata from this file."
+        } else {
+            "If you use this software, please cite it using the metadata from this file."
+        },
+        kind,
+        title: main.summary.title.title.value.clone(),
+        authors: authors(main, owner),
+        version: version.map(str::to_string),
+        date_released: date_released(main),
+        doi: main.doi().map(str::to_string),
+        identifiers: identifiers(main),
+        repository_code,
+        url,
+        abstract_: main.short_description.clone(),
+        references: rest.iter().map(|w| to_reference(w, owner)).collect(),
+    };
+
+    let errors = validate(&cff);
+    if !errors.is_empty() {
+        bail!(
+            "put-code {} does not make a valid CITATION.cff: {}",
+            main.put_code(),
+            errors.join("; ")
+        );
+    }
+
+    Ok(format!(
+        "# Generated by orcid-works-cli from ORCID put-code {}\n{}",
+        main.put_code(),
+        to_yaml(&cff)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use orcid_works_model::OrcidWorkDetailFile;
+
+    const FIXTURE: &str = include_str!("../../tests/fixtures/works.json");
+    const OWNER: Option<&str> = Some("Josiah Carberry");
+
+    fn work(put_code: u64) -> OrcidWorkDetail {
+        let file: OrcidWorkDetailFile = serde_json::from_str(FIXTURE).expect("fixture parses");
+        file.records
+            .into_iter()
+            .find(|w| w.put_code() == put_code)
+            .expect("put-code in fixture")
+    }
+
+    #[test]
+    fn software_without_contributors_cites_the_owner() {
+        let cff = to_cff(&[work(1004), work(1001)], OWNER, Some("1.0"), false).unwrap();
+        assert!(cff.starts_with(
+            "# Generated by orcid-works-cli from ORCID put-code 1004\n\
+             cff-version: \"1.2.0\"\n\
+             message: \"If you use this software, please cite it using the metadata from this file.\"\n\
+             type: software\n\
+             title: \"potfit\"\n\
+             authors:\n  \
+               - family-names: \"Carberry\"\n    \
+                 given-names: \"Josiah\"\n    \
+                 orcid: \"https://orcid.org/0000-0002-1825-0097\"\n\
+             version: \"1.0\"\n\
+             repository-code: \"https://github.com/example/potfit\"\n\
+             references:\n  \
+               - type: article\n"
+        ), "{cff}");
+        assert!(
+            cff.contains("      - family-names: \"Müller\"\n        given-names: \"Jürgen\"\n")
+        );
+        assert!(cff.contains("    year: 2021\n"));
+    }
+
+    #[test]
+    fn work_without_authors_or_owner_is_refused() {
+        let err = to_cff(&[work(1004)], None, None, false).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("authors: at least one author is required"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn bad_release_date_is_refused() {
+        let mut w = work(1001);
+        w.summary.publication_date.as_mut().unwrap().year.value = "21".to_string();
+        let err = to_cff(&[w], OWNER, None, true).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("date-released: 21-02-01 is not YYYY-MM-DD"),
+            "{err}"
+        );
+
+        let cff = to_cff(&[work(1001)], OWNER, None, true).unwrap();
+        assert!(cff.contains("\ndate-released: \"2021-02-01\"\n"), "{cff}");
+    }
+
+    #[test]
+    fn only_software_and_data_sets_unless_forced() {
+        let err = to_cff(&[work(1001)], OWNER, None, false).unwrap_err();
+        assert!(err.to_string().contains("--any-type"), "{err}");
+        assert!(to_cff(&[work(1001)], OWNER, None, true).is_ok());
+
+        let mut data = work(1004);
+        data.summary.r#type = "data-set".to_string();
+        let cff = to_cff(&[data], OWNER, None, false).unwrap();
+        assert!(cff.contains("\ntype: dataset\n"), "{cff}");
+        assert!(cff.contains("If you use this dataset"));
+    }
+
+    #[test]
+    fn strings_are_quoted_and_escaped() {
+        assert_eq!(quoted("yes"), "\"yes\"");
+        assert_eq!(quoted("a: b # c"), "\"a: b # c\"");
+        assert_eq!(quoted("say \"hi\"\\"), "\"say \\\"hi\\\"\\\\\"");
+        assert_eq!(quoted("line\nbreak\ttab"), "\"line\\nbreak\\ttab\"");
+        assert_eq!(quoted("bell\u{7}"), "\"bell\\u0007\"");
+    }
+}
@@ -0,0 +1,220 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use orcid_works_model::{OrcidRecord, OrcidWorkDetail};
+
+use super::{
+    CitationKeys, contributors, credit_name, day, external_id, journal, month, split_name, url,
+    year,
+};
+
+// CSL name object; `literal` is used when a name cannot be split
+#[derive(Serialize)]
+struct CslName {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    family: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    given: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    literal: Option<String>,
+}
+
+#[derive(Serialize)]
+struct CslDate {
+    #[serde(rename = "date-parts")]
+    date_parts: Vec<Vec<u32>>,
+}
+
+// One CSL-JSON item, as consumed by citeproc-js and Pandoc
+#[derive(Serialize)]
+struct CslItem {
+    id: String,
+    #[serde(rename = "type")]
+    kind: &'static str,
+    title: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    author: Vec<CslName>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    editor: Vec<CslName>,
+    #[serde(rename = "container-title", skip_serializing_if = "Option::is_none")]
+    container_title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    issued: Option<CslDate>,
+    #[serde(rename = "DOI", skip_serializing_if = "Option::is_none")]
+    doi: Option<String>,
+    #[serde(rename = "URL", skip_serializing_if = "Option::is_none")]
+    url: Option<String>,
+    #[serde(rename = "ISBN", skip_serializing_if = "Option::is_none")]
+    isbn: Option<String>,
+    #[serde(rename = "ISSN", skip_serializing_if = "Option::is_none")]
+    issn: Option<String>,
+    #[serde(rename = "abstract", skip_serializing_if = "Option::is_none")]
+    abstract_: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    language: Option<String>,
+    // ORCID put-code, kept so items can be traced back to the record
+    note: String,
+}
+
+// CSL 1.0.2 item type for an ORCID work type
+fn csl_type(work_type: &str) -> &'static str {
+    match work_type {
+        "journal-article" | "journal-issue" => "article-journal",
+        "magazine-article" => "article-magazine",
+        "newspaper-article" => "article-newspaper",
+        "preprint" => "article",
+        "book" | "edited-book" | "conference-proceedings" => "book",
+        "book-chapter" => "chapter",
+        "conference-paper" | "conference-abstract" | "conference-poster" => "paper-conference",
+        "dissertation" | "dissertation-thesis" => "thesis",
+        "report" | "working-paper" | "research-technique" | "manual" => "report",
+        "data-set" | "data-management-plan" => "dataset",
+        "software" => "software",
+        "encyclopedia-entry" => "entry-encyclopedia",
+        "dictionary-entry" => "entry-dictionary",
+        "review" | "book-review" => "review",
+        "patent" => "patent",
+        "website" | "online-resource" => "webpage",
+        "lecture-speech" => "speech",
+        "standards-and-policy" => "standard",
+        _ => "document",
+    }
+}
+
+fn names(work: &OrcidWorkDetail, role: &str) -> Vec<CslName> {
+    contributors(work, role)
+        .filter_map(credit_name)
+        .map(|n| {
+            let split = split_name(n);
+            match split.given {
+                Some(given) => CslName {
+                    family: Some(split.family),
+                    given: Some(given),
+                    literal: None,
+                },
+                None => CslName {
+                    family: None,
+                    given: None,
+                    literal: Some(n.to_string()),
+                },
+            }
+        })
+        .collect()
+}
+
+// `[[year]]`, `[[year, month]]` or `[[year, month, day]]`
+fn issued(work: &OrcidWorkDetail) -> Option<CslDate> {
+    let mut parts = vec![year(work)?.parse().ok()?];
+    if let Some(m) = month(work) {
+        parts.push(m);
+        parts.extend(day(work));
+    }
+    Some(CslDate {
+        date_parts: vec![parts],
+    })
+}
+
+fn to_item(work: &OrcidWorkDetail, id: String) -> CslItem {
+    let title = &work.summary.title;
+    CslItem {
+        id,
+        kind: csl_type(&work.summary.r#type),
+        title: match &title.subtitle {
+            Some(sub) => format!("{}: {}", title.title.value, sub.value),
+            None => title.title.value.clone(),
+        },
+        author: names(work, "author"),
+        editor: names(work, "editor"),
+        container_title: journal(work).map(str::to_string),
+        issued: issued(work),
+        doi: work.doi().map(str::to_string),
+        url: url(work).map(str::to_string),
+        isbn: external_id(work, "isbn").map(str::to_string),
+        issn: external_id(work, "issn").map(str::to_string),
+        abstract_: work.short_description.clone(),
+        language: work.language_code.clone(),
+        note: format!("ORCID put-code: {}", work.put_code()),
+    }
+}
+
+// CSL-JSON array; item ids are the same citation keys as in the BibTeX export
+pub(super) fn to_csl_json(works: &[OrcidWorkDetail], keys: &CitationKeys) -> Result<String> {
+    let items: Vec<CslItem> = works
+        .iter()
+        .map(|work| to_item(work, keys.get(work)))
+        .collect();
+    let mut out = serde_json::to_string_pretty(&items).context("serialize CSL-JSON")?;
+    out.push('\n');
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use orcid_works_model::{Citation, OrcidWorkDetailFile};
+
+    use crate::export::{bibtex::to_bibtex, ris::to_ris};
+
+    const FIXTURE: &str = include_str!("../../tests/fixtures/works.json");
+
+    fn fixture() -> Vec<OrcidWorkDetail> {
+        serde_json::from_str::<OrcidWorkDetailFile>(FIXTURE)
+            .expect("fixture parses")
+            .records
+    }
+
+    fn csl_ids(works: &[OrcidWorkDetail], keys: &CitationKeys) -> Vec<String> {
+        let json: serde_json::Value =
+            serde_json::from_str(&to_csl_json(works, keys).unwrap()).unwrap();
+        json.as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item["id"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    fn bibtex_keys(works: &[OrcidWorkDetail], keys: &CitationKeys) -> Vec<String> {
+        to_bibtex(works, keys)
+            .lines()
+            .filter_map(|l| l.strip_prefix('@'))
+            .map(|l| {
+                let open = l.find(['{', '(']).unwrap();
+                l[open + 1..].split(',').next().unwrap().to_string()
+            })
+            .collect()
+    }
+
+    fn ris_ids(works: &[OrcidWorkDetail], keys: &CitationKeys) -> Vec<String> {
+        to_ris(works, keys)
+            .lines()
+            .filter_map(|l| l.strip_prefix("ID  - "))
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn ids_equal_bibtex_and_ris_keys() {
+        let mut works = fixture();
+        // An embedded BibTeX citation with a key of its own
+        works[2].citation = Some(Citation {
+            citation_type: "bibtex".to_string(),
+            citation_value: "@inproceedings{Carberry:2019,\n  title = {Paper}\n}".to_string(),
+        });
+        let keys = CitationKeys::new(&works);
+
+        let ids = csl_ids(&works, &keys);
+        assert_eq!(ids.len(), works.len());
+        assert_eq!(ids, bibtex_keys(&works, &keys));
+        assert_eq!(ids, ris_ids(&works, &keys));
+        assert!(!ids.iter().any(|id| id == "Carberry:2019"));
+    }
+
+    #[test]
+    fn ids_of_a_selection_match_the_full_export() {
+        let works = fixture();
+        let keys = CitationKeys::new(&works);
+        let all = csl_ids(&works, &keys);
+        let some = csl_ids(&works[2..], &keys);
+        assert_eq!(some, all[2..]);
+    }
+}
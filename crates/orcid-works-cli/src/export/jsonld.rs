@@ -0,0 +1,304 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use orcid_works_model::{Contributor, OrcidRecord, OrcidWorkDetail};
+
+use super::{
+    contributors, credit_name, day, doi_url, external_id, journal, month, orcid_host, split_name,
+    url, work_uri, year,
+};
+
+// schema.org `Person`, linked to the ORCID profile when the contributor has one
+#[derive(Serialize)]
+struct Person {
+    #[serde(rename = "@type")]
+    kind: &'static str,
+    name: String,
+    #[serde(rename = "givenName", skip_serializing_if = "Option::is_none")]
+    given_name: Option<String>,
+    #[serde(rename = "familyName", skip_serializing_if = "Option::is_none")]
+    family_name: Option<String>,
+    #[serde(rename = "sameAs", skip_serializing_if = "Option::is_none")]
+    same_as: Option<String>,
+}
+
+// Journal or series the work is part of
+#[derive(Serialize)]
+struct Container {
+    #[serde(rename = "@type")]
+    kind: &'static str,
+    name: String,
+}
+
+#[derive(Serialize)]
+struct Item {
+    #[serde(rename = "@type")]
+    kind: &'static str,
+    // ORCID URI of the work
+    #[serde(rename = "@id")]
+    id: String,
+    name: String,
+    #[serde(
+        rename = "alternativeHeadline",
+        skip_serializing_if = "Option::is_none"
+    )]
+    alternative_headline: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    author: Vec<Person>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    editor: Vec<Person>,
+    #[serde(rename = "datePublished", skip_serializing_if = "Option::is_none")]
+    date_published: Option<String>,
+    #[serde(rename = "isPartOf", skip_serializing_if = "Option::is_none")]
+    is_part_of: Option<Container>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<String>,
+    #[serde(rename = "codeRepository", skip_serializing_if = "Option::is_none")]
+    code_repository: Option<String>,
+    #[serde(rename = "sameAs", skip_serializing_if = "Vec::is_empty")]
+    same_as: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    isbn: Option<String>,
+    #[serde(rename = "abstract", skip_serializing_if = "Option::is_none")]
+    abstract_: Option<String>,
+    #[serde(rename = "inLanguage", skip_serializing_if = "Option::is_none")]
+    in_language: Option<String>,
+}
+
+#[derive(Serialize)]
+struct Document {
+    #[serde(rename = "@context")]
+    context: &'static str,
+    #[serde(rename = "@graph")]
+    graph: Vec<Item>,
+}
+
+// schema.org type for an ORCID work type
+fn schema_type(work_type: &str) -> &'static str {
+    match work_type {
+        "journal-article"
+        | "journal-issue"
+        | "magazine-article"
+        | "newspaper-article"
+        | "conference-paper"
+        | "conference-abstract"
+        | "conference-poster"
+        | "preprint"
+        | "review"
+        | "book-review"
+        | "working-paper"
+        | "report"
+        | "dissertation"
+        | "dissertation-thesis"
+        | "encyclopedia-entry"
+        | "dictionary-entry" => "ScholarlyArticle",
+        "book" | "edited-book" | "conference-proceedings" => "Book",
+        "data-set" => "Dataset",
+        "software" => "SoftwareSourceCode",
+        _ => "CreativeWork",
+    }
+}
+
+// Profile URI of a contributor; `path` alone is completed with the work's host
+fn orcid_uri(work: &OrcidWorkDetail, c: &Contributor) -> Option<String> {
+    let orcid = c.contributor_orcid.as_ref()?;
+    orcid.uri.clone().or_else(|| {
+        let host = orcid.host.as_deref().unwrap_or(orcid_host(work));
+        orcid.path.as_ref().map(|p| format!("https://{host}/{p}"))
+    })
+}
+
+fn people(work: &OrcidWorkDetail, role: &str) -> Vec<Person> {
+    contributors(work, role)
+        .filter_map(|c| {
+            let name = credit_name(c)?;
+            let split = split_name(name);
+            Some(Person {
+                kind: "Person",
+                name: name.to_string(),
+                family_name: split.given.is_some().then_some(split.family),
+                given_name: split.given,
+                same_as: orcid_uri(work, c),
+            })
+        })
+        .collect()
+}
+
+// ISO 8601 date with as much precision as recorded
+fn date_published(work: &OrcidWorkDetail) -> Option<String> {
+    let year = year(work)?;
+    Some(match (month(work), day(work)) {
+        (Some(m), Some(d)) => format!("{year}-{m:02}-{d:02}"),
+        (Some(m), None) => format!("{year}-{m:02}"),
+        _ => year.to_string(),
+    })
+}
+
+fn to_item(work: &OrcidWorkDetail) -> Item {
+    let kind = schema_type(&work.summary.r#type);
+    let url = url(work).map(str::to_string);
+    let title = &work.summary.title;
+    Item {
+        kind,
+        id: work_uri(work),
+        name: title.title.value.clone(),
+        alternative_headline: title.subtitle.as_ref().map(|s| s.value.clone()),
+        author: people(work, "author"),
+        editor: people(work, "editor"),
+        date_published: date_published(work),
+        is_part_of: journal(work).map(|name| Container {
+            kind: if kind == "ScholarlyArticle" {
+                "Periodical"
+            } else {
+                "CreativeWorkSeries"
+            },
+            name: name.to_string(),
+        }),
+        code_repository: url.clone().filter(|_| kind == "SoftwareSourceCode"),
+        url,
+        same_as: work.doi().map(doi_url).into_iter().collect(),
+        isbn: external_id(work, "isbn").map(str::to_string),
+        abstract_: work.short_description.clone(),
+        in_language: work.language_code.clone(),
+    }
+}
+
+// One JSON-LD document with every work in `@graph`, ready for a
+// `<script type="application/ld+json">` block: `<`, `>` and `&` only occur in
+// strings and are written as escapes so a title cannot close the script
+pub(crate) fn to_json_ld(works: &[OrcidWorkDetail]) -> Result<String> {
+    let doc = Document {
+        context: "https://schema.org",
+        graph: works.iter().map(to_item).collect(),
+    };
+    let json = serde_json::to_string_pretty(&doc).context("serialize JSON-LD")?;
+    let mut out = json
+        .replace('<', "\\u003c")
+        .replace('>', "\\u003e")
+        .replace('&', "\\u0026");
+    out.push('\n');
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use orcid_works_model::OrcidWorkDetailFile;
+    use serde_json::Value;
+
+    const FIXTURE: &str = include_str!("../../tests/fixtures/works.json");
+
+    fn records() -> Vec<OrcidWorkDetail> {
+        let file: OrcidWorkDetailFile = serde_json::from_str(FIXTURE).expect("fixture parses");
+        file.records
+    }
+
+    fn graph(works: &[OrcidWorkDetail]) -> Vec<Value> {
+        let doc: Value = serde_json::from_str(&to_json_ld(works).unwrap()).unwrap();
+        assert_eq!(doc["@context"], "https://schema.org");
+        doc["@graph"].as_array().unwrap().clone()
+    }
+
+    #[test]
+    fn work_types_map_to_schema_types() {
+        assert_eq!(schema_type("journal-article"), "ScholarlyArticle");
+        assert_eq!(schema_type("conference-paper"), "ScholarlyArticle");
+        assert_eq!(schema_type("book"), "Book");
+        assert_eq!(schema_type("edited-book"), "Book");
+        assert_eq!(schema_type("data-set"), "Dataset");
+        assert_eq!(schema_type("software"), "SoftwareSourceCode");
+        assert_eq!(schema_type("lecture-speech"), "CreativeWork");
+        assert_eq!(schema_type("other"), "CreativeWork");
+
+        // The fixture's journal article, edited book, conference paper and software
+        let types: Vec<Value> = graph(&records())
+            .iter()
+            .map(|item| item["@type"].clone())
+            .collect();
+        assert_eq!(
+            types,
+            [
+                "ScholarlyArticle",
+                "Book",
+                "ScholarlyArticle",
+                "SoftwareSourceCode"
+            ]
+        );
+    }
+
+    #[test]
+    fn ids_are_work_uris() {
+        let ids: Vec<String> = graph(&records())
+            .iter()
+            .map(|item| item["@id"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(
+            ids,
+            [1001, 1002, 1003, 1004]
+                .map(|pc| format!("https://orcid.org/0000-0002-1825-0097/work/{pc}"))
+        );
+    }
+
+    #[test]
+    fn people_link_their_orcid_profile() {
+        let items = graph(&records());
+        let authors = items[0]["author"].as_array().unwrap();
+        assert_eq!(authors[0]["@type"], "Person");
+        assert_eq!(authors[0]["name"], "Josiah Carberry");
+        assert_eq!(authors[0]["givenName"], "Josiah");
+        assert_eq!(authors[0]["familyName"], "Carberry");
+        assert_eq!(
+            authors[0]["sameAs"],
+            "https://orcid.org/0000-0002-1825-0097"
+        );
+        assert_eq!(authors[1]["name"], "Müller, Jürgen");
+        assert!(authors[1].get("sameAs").is_none());
+
+        // A bare `path` is completed with the work's registry
+        let mut records = records();
+        let orcid = records[0]
+            .contributors
+            .as_mut()
+            .unwrap()
+            .contributor
+            .as_mut()
+            .unwrap()[0]
+            .contributor_orcid
+            .as_mut()
+            .unwrap();
+        orcid.uri = None;
+        orcid.host = Some("sandbox.orcid.org".to_string());
+        let items = graph(&records);
+        assert_eq!(
+            items[0]["author"][0]["sameAs"],
+            "https://sandbox.orcid.org/0000-0002-1825-0097"
+        );
+    }
+
+    #[test]
+    fn doi_is_a_same_as_link() {
+        let items = graph(&records());
+        assert_eq!(
+            items[0]["sameAs"],
+            serde_json::json!(["https://doi.org/10.5555/12345678"])
+        );
+        assert!(items[3].get("sameAs").is_none());
+        assert_eq!(
+            items[3]["codeRepository"],
+            "https://github.com/example/potfit"
+        );
+    }
+
+    #[test]
+    fn markup_characters_cannot_close_the_script_block() {
+        let mut records = records();
+        records[0].summary.title.title.value = "</script><b>R&D</b>".to_string();
+
+        let json = to_json_ld(&records).unwrap();
+        assert!(!json.contains('<') && !json.contains('>') && !json.contains('&'));
+        assert!(json.contains("\\u003c/script\\u003e\\u003cb\\u003eR\\u0026D"));
+
+        let doc: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(doc["@graph"][0]["name"], "</script><b>R&D</b>");
+    }
+}
@@ -0,0 +1,263 @@
+use std::fmt::Write;
+
+use orcid_works_model::{OrcidRecord, OrcidWorkDetail};
+
+use super::{
+    CitationKeys, contributors, credit_name, day, external_id, journal, month, split_name, url,
+    year,
+};
+
+// RIS type for an ORCID work type, and the tag holding `journal-title`
+fn ris_type(work_type: &str) -> (&'static str, &'static str) {
+    match work_type {
+        "journal-article" | "journal-issue" | "review" => ("JOUR", "JO"),
+        "magazine-article" => ("MGZN", "JO"),
+        "newspaper-article" => ("NEWS", "JO"),
+        "book" => ("BOOK", "T2"),
+        "edited-book" => ("EDBOOK", "T2"),
+        "book-chapter" => ("CHAP", "T2"),
+        "conference-paper" | "conference-abstract" | "conference-poster" => ("CPAPER", "T2"),
+        "conference-proceedings" => ("CONF", "T2"),
+        "dissertation" | "dissertation-thesis" => ("THES", "T2"),
+        "report" | "working-paper" => ("RPRT", "T2"),
+        "preprint" => ("UNPB", "T2"),
+        "data-set" => ("DATA", "T2"),
+        "software" => ("COMP", "T2"),
+        "patent" => ("PAT", "T2"),
+        "encyclopedia-entry" => ("ENCYC", "T2"),
+        "dictionary-entry" => ("DICT", "T2"),
+        "website" | "online-resource" => ("ELEC", "T2"),
+        _ => ("GEN", "T2"),
+    }
+}
+
+// RIS values are single lines
+fn line(out: &mut String, tag: &str, value: &str) {
+    let value = value.split_whitespace().collect::<Vec<_>>().join(" ");
+    if !value.is_empty() {
+        let _ = writeln!(out, "{tag}  - {value}");
+    }
+}
+
+// `AU`/`ED` values: "Family, Given", or the credit name as is when it cannot be split
+fn names<'a>(work: &'a OrcidWorkDetail, role: &'a str) -> impl Iterator<Item = String> + 'a {
+    contributors(work, role).filter_map(credit_name).map(|n| {
+        let split = split_name(n);
+        match split.given {
+            Some(given) => format!("{}, {given}", split.family),
+            None => n.to_string(),
+        }
+    })
+}
+
+// `DA` value: "YYYY/MM/DD/" with unknown parts left empty
+fn date(work: &OrcidWorkDetail) -> Option<String> {
+    let year = year(work)?;
+    let (month, day) = match month(work) {
+        Some(m) => (
+            format!("{m:02}"),
+            day(work).map(|d| format!("{d:02}")).unwrap_or_default(),
+        ),
+        None => (String::new(), String::new()),
+    };
+    Some(format!("{year}/{month}/{day}/"))
+}
+
+pub(super) fn to_ris(works: &[OrcidWorkDetail], keys: &CitationKeys) -> String {
+    let mut out = String::new();
+
+    for work in works {
+        let key = keys.get(work);
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        let (kind, container) = ris_type(&work.summary.r#type);
+        line(&mut out, "TY", kind);
+        line(&mut out, "ID", &key);
+        for author in names(work, "author") {
+            line(&mut out, "AU", &author);
+        }
+        for editor in names(work, "editor") {
+            line(&mut out, "ED", &editor);
+        }
+        let title = &work.summary.title;
+        match &title.subtitle {
+            Some(sub) => line(
+                &mut out,
+                "TI",
+                &format!("{}: {}", title.title.value, sub.value),
+            ),
+            None => line(&mut out, "TI", &title.title.value),
+        }
+        if let Some(journal) = journal(work) {
+            line(&mut out, container, journal);
+        }
+        if let Some(year) = year(work) {
+            line(&mut out, "PY", year);
+        }
+        if let Some(date) = date(work) {
+            line(&mut out, "DA", &date);
+        }
+        if let Some(doi) = work.doi() {
+            line(&mut out, "DO", doi);
+        }
+        if let Some(url) = url(work) {
+            line(&mut out, "UR", url);
+        }
+        for id_type in ["isbn", "issn"] {
+            if let Some(value) = external_id(work, id_type) {
+                line(&mut out, "SN", value);
+            }
+        }
+        if let Some(language) = &work.language_code {
+            line(&mut out, "LA", language);
+        }
+        if let Some(abstract_) = &work.short_description {
+            line(&mut out, "AB", abstract_);
+        }
+        line(
+            &mut out,
+            "N1",
+            &format!("ORCID put-code: {}", work.put_code()),
+        );
+        out.push_str("ER  - \n");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use orcid_works_model::OrcidWorkDetailFile;
+
+    const FIXTURE: &str = include_str!("../../tests/fixtures/works.json");
+
+    fn fixture() -> Vec<OrcidWorkDetail> {
+        serde_json::from_str::<OrcidWorkDetailFile>(FIXTURE)
+            .expect("fixture parses")
+            .records
+    }
+
+    // Minimal RIS reader: one list of (tag, value) pairs per record
+    fn parse(ris: &str) -> Vec<Vec<(String, String)>> {
+        let mut records = vec![];
+        let mut current: Vec<(String, String)> = vec![];
+        for l in ris.lines().filter(|l| !l.is_empty()) {
+            let (tag, value) = l.split_once("  - ").expect("tag line");
+            assert_eq!(tag.len(), 2, "bad tag in {l:?}");
+            if tag == "ER" {
+                records.push(std::mem::take(&mut current));
+            } else {
+                current.push((tag.to_string(), value.to_string()));
+            }
+        }
+        assert!(current.is_empty(), "record without ER");
+        records
+    }
+
+    fn all<'a>(record: &'a [(String, String)], tag: &str) -> Vec<&'a str> {
+        record
+            .iter()
+            .filter(|(t, _)| t == tag)
+            .map(|(_, v)| v.as_str())
+            .collect()
+    }
+
+    fn one<'a>(record: &'a [(String, String)], tag: &str) -> Option<&'a str> {
+        let values = all(record, tag);
+        assert!(values.len() <= 1, "{tag} repeated");
+        values.first().copied()
+    }
+
+    #[test]
+    fn round_trips_fixture_fields() {
+        let works = fixture();
+        let parsed = parse(&to_ris(&works, &CitationKeys::new(&works)));
+        assert_eq!(parsed.len(), works.len());
+
+        for (work, record) in works.iter().zip(&parsed) {
+            assert_eq!(record[0].0, "TY", "TY must come first");
+            let (kind, container) = ris_type(&work.summary.r#type);
+            assert_eq!(one(record, "TY"), Some(kind));
+            assert_eq!(one(record, "DO"), work.doi());
+            assert_eq!(one(record, "UR"), url(work));
+            assert_eq!(one(record, container), journal(work));
+            assert_eq!(one(record, "PY"), year(work));
+            assert_eq!(one(record, "LA"), work.language_code.as_deref());
+            assert_eq!(
+                one(record, "N1"),
+                Some(format!("ORCID put-code: {}", work.put_code()).as_str())
+            );
+            assert!(
+                one(record, "TI")
+                    .unwrap()
+                    .starts_with(&work.summary.title.title.value)
+            );
+            let abstract_ = work
+                .short_description
+                .as_ref()
+                .map(|a| a.split_whitespace().collect::<Vec<_>>().join(" "));
+            assert_eq!(one(record, "AB"), abstract_.as_deref());
+            assert_eq!(
+                all(record, "AU").len() + all(record, "ED").len(),
+                contributors(work, "author").count() + contributors(work, "editor").count()
+            );
+        }
+    }
+
+    #[test]
+    fn writes_names_dates_and_ids() {
+        let works = fixture();
+        let parsed = parse(&to_ris(&works, &CitationKeys::new(&works)));
+
+        let article = &parsed[0];
+        assert_eq!(one(article, "ID"), Some("carberry2021psychoceramics"));
+        assert_eq!(all(article, "AU"), ["Carberry, Josiah", "Müller, Jürgen"]);
+        assert_eq!(
+            one(article, "TI"),
+            Some("Psychoceramics and the cracked pot: A review")
+        );
+        assert_eq!(one(article, "DA"), Some("2021/02/01/"));
+        assert_eq!(one(article, "SN"), Some("1234-5678"));
+        assert_eq!(
+            one(article, "AB"),
+            Some(
+                "Cracked pots are studied. A second line; with punctuation, \"quotes\" and Ünïcödé."
+            )
+        );
+
+        let book = &parsed[1];
+        assert_eq!(one(book, "TY"), Some("EDBOOK"));
+        assert!(all(book, "AU").is_empty());
+        assert_eq!(all(book, "ED"), ["van Beethoven, Ludwig", "Lovelace, Ada"]);
+        assert_eq!(one(book, "DA"), Some("2020///"));
+        assert_eq!(one(book, "SN"), Some("978-3-16-148410-0"));
+
+        let paper = &parsed[2];
+        assert_eq!(one(paper, "TY"), Some("CPAPER"));
+        assert_eq!(all(paper, "AU"), ["Carberry, Josiah", "Hopper, Grace"]);
+        assert_eq!(
+            one(paper, "T2"),
+            Some("Proceedings of the Ceramics Conference")
+        );
+        assert_eq!(one(paper, "DA"), Some("2019/07//"));
+
+        let software = &parsed[3];
+        assert_eq!(one(software, "TY"), Some("COMP"));
+        assert_eq!(one(software, "ID"), Some("orcid1004"));
+        assert_eq!(one(software, "PY"), None);
+        assert_eq!(one(software, "DA"), None);
+        assert!(all(software, "AU").is_empty());
+    }
+
+    #[test]
+    fn output_is_stable() {
+        let works = fixture();
+        assert_eq!(
+            to_ris(&works, &CitationKeys::new(&works)),
+            to_ris(&works, &CitationKeys::new(&works))
+        );
+        assert!(to_ris(&works, &CitationKeys::new(&works)).ends_with("ER  - \n"));
+        assert_eq!(to_ris(&[], &CitationKeys::new(&[])), "");
+    }
+}
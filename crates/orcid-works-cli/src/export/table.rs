@@ -0,0 +1,246 @@
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+
+use orcid_works_model::{Contributor, OrcidRecord, OrcidWorkDetail};
+
+use super::{credit_name, journal, year};
+
+// Columns of the CSV/TSV export, in the order given on the command line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub(crate) enum Column {
+    PutCode,
+    Title,
+    Type,
+    Year,
+    Journal,
+    Doi,
+    Contributors,
+    ContributorOrcid,
+    ContributorRole,
+    Source,
+    Visibility,
+}
+
+// Default columns of `--columns`
+pub(crate) const DEFAULT_COLUMNS: [Column; 9] = [
+    Column::PutCode,
+    Column::Title,
+    Column::Type,
+    Column::Year,
+    Column::Journal,
+    Column::Doi,
+    Column::Contributors,
+    Column::Source,
+    Column::Visibility,
+];
+
+// What one CSV/TSV row stands for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub(crate) enum RowMode {
+    Work,
+    Contributor,
+}
+
+// Separator of several values in one cell (work rows)
+const LIST_SEPARATOR: &str = "; ";
+
+fn all_contributors(work: &OrcidWorkDetail) -> Vec<&Contributor> {
+    work.contributors
+        .iter()
+        .flat_map(|c| c.contributor.iter().flatten())
+        .collect()
+}
+
+fn contributor_name(c: &Contributor) -> &str {
+    credit_name(c).unwrap_or_default()
+}
+
+fn contributor_orcid(c: &Contributor) -> &str {
+    c.contributor_orcid
+        .as_ref()
+        .and_then(|o| o.path.as_deref())
+        .unwrap_or_default()
+}
+
+fn contributor_role(c: &Contributor) -> &str {
+    c.contributor_attributes
+        .as_ref()
+        .and_then(|a| a.contributor_role.as_deref())
+        .unwrap_or_default()
+}
+
+fn joined(contributors: &[&Contributor], field: fn(&Contributor) -> &str) -> String {
+    contributors
+        .iter()
+        .map(|c| field(c))
+        .collect::<Vec<_>>()
+        .join(LIST_SEPARATOR)
+}
+
+// One cell; `contributors` are the contributors covered by the row (all of
+// them for work rows, a single one for contributor rows)
+fn cell(work: &OrcidWorkDetail, contributors: &[&Contributor], column: Column) -> String {
+    let s = &work.summary;
+    match column {
+        Column::PutCode => work.put_code().to_string(),
+        Column::Title => s.title.title.value.clone(),
+        Column::Type => s.r#type.clone(),
+        Column::Year => year(work).unwrap_or_default().to_string(),
+        Column::Journal => journal(work).unwrap_or_default().to_string(),
+        Column::Doi => work.doi().unwrap_or_default().to_string(),
+        Column::Contributors => joined(contributors, contributor_name),
+        Column::ContributorOrcid => joined(contributors, contributor_orcid),
+        Column::ContributorRole => joined(contributors, contributor_role),
+        Column::Source => s
+            .source
+            .source_name
+            .as_ref()
+            .map(|n| n.value.clone())
+            .unwrap_or_default(),
+        Column::Visibility => s.visibility.clone(),
+    }
+}
+
+fn header(column: Column) -> &'static str {
+    match column {
+        Column::PutCode => "put-code",
+        Column::Title => "title",
+        Column::Type => "type",
+        Column::Year => "year",
+        Column::Journal => "journal",
+        Column::Doi => "doi",
+        Column::Contributors => "contributors",
+        Column::ContributorOrcid => "contributor-orcid",
+        Column::ContributorRole => "contributor-role",
+        Column::Source => "source",
+        Column::Visibility => "visibility",
+    }
+}
+
+// TSV has no quoting: tabs and line breaks inside a field become spaces
+fn tsv_field(s: String) -> String {
+    if s.contains(['\t', '\n', '\r']) {
+        s.replace("\r\n", " ").replace(['\t', '\n', '\r'], " ")
+    } else {
+        s
+    }
+}
+
+// Delimited table with a header row. CSV fields containing the delimiter,
+// quotes or line breaks are quoted; TSV fields are never quoted and have
+// their tabs and line breaks replaced instead. Contributor rows repeat the
+// work's cells for each contributor; a work without contributors still gets
+// one row.
+pub(super) fn to_table(
+    works: &[OrcidWorkDetail],
+    columns: &[Column],
+    rows: RowMode,
+    delimiter: u8,
+) -> Result<String> {
+    let tsv = delimiter == b'\t';
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(delimiter)
+        .quote_style(if tsv {
+            csv::QuoteStyle::Never
+        } else {
+            csv::QuoteStyle::Necessary
+        })
+        .from_writer(Vec::new());
+    writer.write_record(columns.iter().map(|c| header(*c)))?;
+
+    let row = |work: &OrcidWorkDetail, contributors: &[&Contributor]| -> Vec<String> {
+        columns
+            .iter()
+            .map(|c| {
+                let cell = cell(work, contributors, *c);
+                if tsv { tsv_field(cell) } else { cell }
+            })
+            .collect()
+    };
+    for work in works {
+        let contributors = all_contributors(work);
+        match rows {
+            RowMode::Work => writer.write_record(row(work, &contributors))?,
+            RowMode::Contributor if contributors.is_empty() => {
+                writer.write_record(row(work, &[]))?;
+            }
+            RowMode::Contributor => {
+                for contributor in &contributors {
+                    writer.write_record(row(work, &[*contributor]))?;
+                }
+            }
+        }
+    }
+
+    let bytes = writer.into_inner().context("flush table")?;
+    String::from_utf8(bytes).context("table is not UTF-8")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use orcid_works_model::OrcidWorkDetailFile;
+
+    const FIXTURE: &str = include_str!("../../tests/fixtures/works.json");
+    const COLUMNS: [Column; 3] = [Column::PutCode, Column::Title, Column::Contributors];
+
+    fn works() -> Vec<OrcidWorkDetail> {
+        let file: OrcidWorkDetailFile = serde_json::from_str(FIXTURE).expect("fixture parses");
+        file.records
+    }
+
+    #[test]
+    fn csv_quotes_separators_quotes_and_line_breaks() {
+        let mut works = works();
+        works.truncate(1);
+        works[0].summary.title.title.value = "Pots, \"cracked\"\nand whole".to_string();
+
+        let csv = to_table(&works, &COLUMNS, RowMode::Work, b',').unwrap();
+        assert_eq!(
+            csv,
+            "put-code,title,contributors\n\
+             1001,\"Pots, \"\"cracked\"\"\nand whole\",\"Josiah Carberry; Müller, Jürgen\"\n"
+        );
+
+        let mut reader = csv::Reader::from_reader(csv.as_bytes());
+        let record = reader.records().next().unwrap().unwrap();
+        assert_eq!(&record[1], "Pots, \"cracked\"\nand whole");
+        assert_eq!(&record[2], "Josiah Carberry; Müller, Jürgen");
+    }
+
+    #[test]
+    fn tsv_replaces_tabs_and_line_breaks() {
+        let mut works = works();
+        works.truncate(1);
+        works[0].summary.title.title.value = "Pots\tand \"cracked\"\r\nware\rnow".to_string();
+
+        let tsv = to_table(&works, &COLUMNS, RowMode::Work, b'\t').unwrap();
+        assert_eq!(
+            tsv,
+            "put-code\ttitle\tcontributors\n\
+             1001\tPots and \"cracked\" ware now\tJosiah Carberry; Müller, Jürgen\n"
+        );
+    }
+
+    #[test]
+    fn contributor_rows_repeat_the_work() {
+        let columns = [
+            Column::PutCode,
+            Column::Contributors,
+            Column::ContributorOrcid,
+            Column::ContributorRole,
+        ];
+        let tsv = to_table(&works(), &columns, RowMode::Contributor, b'\t').unwrap();
+        let lines: Vec<&str> = tsv.lines().collect();
+        assert_eq!(
+            lines[..3],
+            [
+                "put-code\tcontributors\tcontributor-orcid\tcontributor-role",
+                "1001\tJosiah Carberry\t0000-0002-1825-0097\tauthor",
+                "1001\tMüller, Jürgen\t\tauthor",
+            ]
+        );
+        // The software work has no contributors and still gets a row
+        assert_eq!(lines.last(), Some(&"1004\t\t\t"));
+    }
+}
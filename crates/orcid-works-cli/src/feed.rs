@@ -0,0 +1,486 @@
+use anyhow::{Context, Result, bail};
+use clap::{Args, ValueEnum};
+use std::{
+    cmp::Reverse,
+    fmt::Write as _,
+    io::Write,
+    path::PathBuf,
+    time::{Duration, UNIX_EPOCH},
+};
+use tracing::info;
+
+use orcid_works_model::{OrcidRecord, OrcidWorkDetail, OrcidWorkDetailFile};
+
+use crate::export::{
+    contributors, credit_name, doi_url, orcid_host, owner_id, owner_name, url, work_uri,
+};
+use crate::io::{read_required_records, write_text};
+
+// Feed formats of the `feed` subcommand
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub(crate) enum FeedFormat {
+    Atom,
+    Rss,
+}
+
+// `feed` subcommand: Atom or RSS feed of the most recently added works
+#[derive(Args)]
+pub(crate) struct FeedArgs {
+    #[arg(help = "Work-details JSON written by a sync")]
+    input: PathBuf,
+
+    #[arg(
+        short = 'f',
+        long = "format",
+        value_enum,
+        default_value_t = FeedFormat::Atom,
+        help = "Feed format"
+    )]
+    format: FeedFormat,
+
+    #[arg(long = "title", default_value = "Publications", help = "Feed title")]
+    title: String,
+
+    #[arg(
+        long = "author",
+        help = "Feed author (Atom) [default: the profile owner's name from the works' contributors or, failing that, from the source of works they added themselves; else their ORCID iD, else the title]"
+    )]
+    author: Option<String>,
+
+    #[arg(
+        long = "link",
+        help = "Website the feed belongs to; also the Atom feed id [default: the ORCID profile]"
+    )]
+    link: Option<String>,
+
+    #[arg(
+        long = "limit",
+        default_value_t = 50,
+        help = "Number of newest works in the feed; 0 for all"
+    )]
+    limit: usize,
+
+    #[arg(
+        short = 'o',
+        long = "out",
+        help = "Output file (parent dirs are created; left untouched if unchanged); printed to stdout if omitted"
+    )]
+    out: Option<PathBuf>,
+}
+
+// Escape text for XML element content and attribute values
+fn xml(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            // Control characters are not allowed in XML 1.0
+            c if c.is_control() && !matches!(c, '\n' | '\t' | '\r') => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+// Civil date (year, month, day) of a day count since 1970-01-01
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+// RFC 3339 timestamp (Atom) of an ORCID epoch-millis value
+fn rfc3339(millis: u64) -> String {
+    let secs = (millis / 1000) as i64;
+    let (y, m, d) = civil_from_days(secs.div_euclid(86_400));
+    let t = secs.rem_euclid(86_400);
+    format!(
+        "{y:04}-{m:02}-{d:02}T{:02}:{:02}:{:02}Z",
+        t / 3600,
+        t / 60 % 60,
+        t % 60
+    )
+}
+
+// RFC 822 date (RSS) of an ORCID epoch-millis value
+fn rfc822(millis: u64) -> String {
+    httpdate::fmt_http_date(UNIX_EPOCH + Duration::from_millis(millis))
+}
+
+// Link of an entry: the DOI, else the work's URL
+fn entry_link(work: &OrcidWorkDetail) -> Option<String> {
+    work.doi()
+        .map(doi_url)
+        .or_else(|| url(work).map(str::to_string))
+}
+
+fn authors(work: &OrcidWorkDetail) -> Vec<&str> {
+    contributors(work, "author")
+        .filter_map(credit_name)
+        .collect()
+}
+
+// ORCID profile of the first work, on its registry; used when no --link is given
+fn profile_uri(works: &[&OrcidWorkDetail]) -> Option<String> {
+    let work = works.first()?;
+    let owner = Some(owner_id(work)).filter(|id| !id.is_empty())?;
+    Some(format!("https://{}/{owner}", orcid_host(work)))
+}
+
+// Feed-level author, which always exists: Atom needs one for the entries
+// without contributors. `--author`, else the profile owner's name, else
+// their iD, else the feed title.
+fn feed_author<'a>(
+    author: Option<&'a str>,
+    records: &'a [OrcidWorkDetail],
+    title: &'a str,
+) -> &'a str {
+    author
+        .or_else(|| owner_name(records))
+        .or_else(|| records.iter().map(owner_id).find(|id| !id.is_empty()))
+        .unwrap_or(title)
+}
+
+fn to_atom(works: &[&OrcidWorkDetail], title: &str, link: &str, author: &str) -> String {
+    // The newest modification rather than the current time (and no version in
+    // <generator>), so that an unchanged work-details file yields the same feed
+    let updated = works.iter().map(|w| w.last_modified()).max().unwrap_or(0);
+
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    out.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    let _ = writeln!(out, "  <id>{}</id>", xml(link));
+    let _ = writeln!(out, "  <title>{}</title>", xml(title));
+    let _ = writeln!(out, "  <link rel=\"alternate\" href=\"{}\"/>", xml(link));
+    let _ = writeln!(out, "  <updated>{}</updated>", rfc3339(updated));
+    // Covers entries without contributors; Atom requires an author on the
+    // feed or on every entry
+    let _ = writeln!(out, "  <author><name>{}</name></author>", xml(author));
+    out.push_str("  <generator>orcid-works-cli</generator>\n");
+    for work in works {
+        out.push_str("  <entry>\n");
+        let _ = writeln!(out, "    <id>{}</id>", xml(&work_uri(work)));
+        let _ = writeln!(
+            out,
+            "    <title>{}</title>",
+            xml(&work.summary.title.title.value)
+        );
+        if let Some(href) = entry_link(work) {
+            let _ = writeln!(out, "    <link rel=\"alternate\" href=\"{}\"/>", xml(&href));
+        }
+        let _ = writeln!(
+            out,
+            "    <published>{}</published>",
+            rfc3339(work.summary.created_date.value)
+        );
+        let _ = writeln!(
+            out,
+            "    <updated>{}</updated>",
+            rfc3339(work.last_modified())
+        );
+        for name in authors(work) {
+            let _ = writeln!(out, "    <author><name>{}</name></author>", xml(name));
+        }
+        let _ = writeln!(
+            out,
+            "    <category term=\"{}\"/>",
+            xml(&work.summary.r#type)
+        );
+        if let Some(summary) = &work.short_description {
+            let _ = writeln!(out, "    <summary>{}</summary>", xml(summary));
+        }
+        out.push_str("  </entry>\n");
+    }
+    out.push_str("</feed>\n");
+    out
+}
+
+fn to_rss(works: &[&OrcidWorkDetail], title: &str, link: &str) -> String {
+    let updated = works.iter().map(|w| w.last_modified()).max().unwrap_or(0);
+
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    out.push_str("<rss version=\"2.0\">\n  <channel>\n");
+    let _ = writeln!(out, "    <title>{}</title>", xml(title));
+    let _ = writeln!(out, "    <link>{}</link>", xml(link));
+    let _ = writeln!(out, "    <description>{}</description>", xml(title));
+    let _ = writeln!(
+        out,
+        "    <lastBuildDate>{}</lastBuildDate>",
+        rfc822(updated)
+    );
+    out.push_str("    <generator>orcid-works-cli</generator>\n");
+    for work in works {
+        out.push_str("    <item>\n");
+        let _ = writeln!(
+            out,
+            "      <title>{}</title>",
+            xml(&work.summary.title.title.value)
+        );
+        if let Some(href) = entry_link(work) {
+            let _ = writeln!(out, "      <link>{}</link>", xml(&href));
+        }
+        let _ = writeln!(
+            out,
+            "      <guid isPermaLink=\"false\">{}</guid>",
+            xml(&work_uri(work))
+        );
+        let _ = writeln!(
+            out,
+            "      <pubDate>{}</pubDate>",
+            rfc822(work.summary.created_date.value)
+        );
+        let _ = writeln!(
+            out,
+            "      <category>{}</category>",
+            xml(&work.summary.r#type)
+        );
+        if let Some(summary) = &work.short_description {
+            let _ = writeln!(out, "      <description>{}</description>", xml(summary));
+        }
+        out.push_str("    </item>\n");
+    }
+    out.push_str("  </channel>\n</rss>\n");
+    out
+}
+
+pub(crate) fn run_feed(args: &FeedArgs) -> Result<()> {
+    let file: OrcidWorkDetailFile = read_required_records(&args.input)
+        .with_context(|| format!("open the work-details JSON from {}", args.input.display()))?;
+
+    // Newest additions first; the put-code breaks ties so the order is stable
+    let mut works: Vec<&OrcidWorkDetail> = file.records.iter().collect();
+    works.sort_by_key(|w| Reverse((w.summary.created_date.value, w.put_code())));
+    if args.limit > 0 {
+        works.truncate(args.limit);
+    }
+
+    let Some(link) = args.link.clone().or_else(|| profile_uri(&works)) else {
+        bail!(
+            "no works in {} to take the ORCID profile from; pass --link for an empty feed",
+            args.input.display()
+        );
+    };
+    let author = feed_author(args.author.as_deref(), &file.records, &args.title);
+    let text = match args.format {
+        FeedFormat::Atom => to_atom(&works, &args.title, &link, author),
+        FeedFormat::Rss => to_rss(&works, &args.title, &link),
+    };
+
+    match &args.out {
+        Some(path) => {
+            if std::fs::read_to_string(path).is_ok_and(|old| old == text) {
+                info!(path = %path.display(), "feed unchanged; not rewritten");
+                return Ok(());
+            }
+            write_text(path, &text).with_context(|| format!("write feed to {}", path.display()))
+        }
+        None => {
+            let mut stdout = std::io::stdout().lock();
+            stdout.write_all(text.as_bytes())?;
+            Ok(stdout.flush()?)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use orcid_works_model::Value;
+
+    const FIXTURE: &str = include_str!("../tests/fixtures/works.json");
+    const DAY_MS: u64 = 86_400_000;
+
+    fn fixture() -> OrcidWorkDetailFile {
+        serde_json::from_str(FIXTURE).expect("fixture parses")
+    }
+
+    #[test]
+    fn civil_dates_around_the_epoch() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert_eq!(civil_from_days(-365), (1969, 1, 1));
+        // 1900 is not a leap year, 2000 is
+        assert_eq!(civil_from_days(-25_508), (1900, 3, 1));
+        assert_eq!(civil_from_days(-25_509), (1900, 2, 28));
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+    }
+
+    #[test]
+    fn civil_dates_after_2100() {
+        // 2100 is not a leap year, 2400 is
+        assert_eq!(civil_from_days(47_540), (2100, 2, 28));
+        assert_eq!(civil_from_days(47_541), (2100, 3, 1));
+        assert_eq!(civil_from_days(157_113), (2400, 2, 29));
+        assert_eq!(civil_from_days(2_932_896), (9999, 12, 31));
+    }
+
+    #[test]
+    fn rfc3339_timestamps() {
+        assert_eq!(rfc3339(0), "1970-01-01T00:00:00Z");
+        assert_eq!(rfc3339(999), "1970-01-01T00:00:00Z");
+        assert_eq!(rfc3339(1_709_251_199_000), "2024-02-29T23:59:59Z");
+        assert_eq!(rfc3339(1_709_251_200_000), "2024-03-01T00:00:00Z");
+        assert_eq!(rfc3339(4_107_542_400_000), "2100-03-01T00:00:00Z");
+    }
+
+    #[test]
+    fn rfc3339_agrees_with_http_dates() {
+        const MONTHS: [&str; 12] = [
+            "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+        ];
+        // Every 997th day up to year 9999, at a varying time of day
+        for n in 0..2942 {
+            let millis = n * 997 * DAY_MS + n * 29_347_001 % DAY_MS;
+            let (date, time) = rfc3339(millis)
+                .split_once('T')
+                .map(|(d, t)| (d.to_string(), t.to_string()))
+                .unwrap();
+            let [y, m, d] = date.splitn(3, '-').collect::<Vec<_>>()[..] else {
+                panic!("{date}");
+            };
+            let month = MONTHS[m.parse::<usize>().unwrap() - 1];
+            let http = rfc822(millis);
+            assert!(
+                http.ends_with(&format!(
+                    "{d} {month} {y} {} GMT",
+                    time.trim_end_matches('Z')
+                )),
+                "{millis}: {date}T{time} vs {http}"
+            );
+        }
+    }
+
+    // Atom: every entry has an author of its own or inherits the feed's
+    fn assert_every_entry_has_an_author(feed: &str) {
+        let (head, entries) = feed.split_once("  <entry>").expect("entries");
+        if head.contains("\n  <author>") {
+            return;
+        }
+        for entry in entries.split("  <entry>") {
+            assert!(entry.contains("    <author>"), "no author for {entry}");
+        }
+    }
+
+    #[test]
+    fn feed_author_falls_back_to_the_owner() {
+        let mut file = fixture();
+        assert_eq!(
+            feed_author(Some("J. Carberry"), &file.records, "Publications"),
+            "J. Carberry"
+        );
+        assert_eq!(
+            feed_author(None, &file.records, "Publications"),
+            "Josiah Carberry"
+        );
+
+        // No name anywhere: the owner's iD
+        for work in &mut file.records {
+            work.contributors = None;
+            work.summary.source.source_name = None;
+        }
+        assert_eq!(
+            feed_author(None, &file.records, "Publications"),
+            "0000-0002-1825-0097"
+        );
+        // No works at all: the title
+        assert_eq!(feed_author(None, &[], "Publications"), "Publications");
+
+        let works: Vec<&OrcidWorkDetail> = file.records.iter().collect();
+        let author = feed_author(None, &file.records, "Publications");
+        let feed = to_atom(&works, "Publications", "https://example.org/", author);
+        assert!(
+            feed.contains("  <author><name>0000-0002-1825-0097</name></author>\n  <generator>"),
+            "{feed}"
+        );
+        assert_every_entry_has_an_author(&feed);
+    }
+
+    #[test]
+    fn feed_author_falls_back_to_the_owners_source_name() {
+        let mut file = fixture();
+        for work in &mut file.records {
+            work.contributors = None;
+            if work.put_code() != 1002 {
+                work.summary.source.source_name = Some(Value {
+                    value: "Josiah S. Carberry".to_string(),
+                });
+            }
+        }
+        assert_eq!(
+            feed_author(None, &file.records, "Publications"),
+            "Josiah S. Carberry"
+        );
+
+        // The name of another source (Crossref on 1002) is never taken
+        for work in &mut file.records {
+            if work.put_code() != 1002 {
+                work.summary.source.source_name = None;
+            }
+        }
+        assert_eq!(
+            feed_author(None, &file.records, "Publications"),
+            "0000-0002-1825-0097"
+        );
+    }
+
+    #[test]
+    fn software_without_contributors_inherits_the_feed_author() {
+        let file = fixture();
+        let works: Vec<&OrcidWorkDetail> = file.records.iter().collect();
+        let author = feed_author(None, &file.records, "Publications");
+        let feed = to_atom(&works, "Publications", "https://example.org/", author);
+        assert!(feed.contains("<id>https://orcid.org/0000-0002-1825-0097/work/1004</id>"));
+        assert_every_entry_has_an_author(&feed);
+    }
+
+    #[test]
+    fn empty_selection_has_no_profile_link() {
+        assert_eq!(profile_uri(&[]), None);
+    }
+
+    #[test]
+    fn links_use_the_registry_of_the_data() {
+        let mut file = fixture();
+        let works: Vec<&OrcidWorkDetail> = file.records.iter().collect();
+        assert_eq!(
+            profile_uri(&works).as_deref(),
+            Some("https://orcid.org/0000-0002-1825-0097")
+        );
+
+        for work in &mut file.records {
+            let source = &mut work.summary.source;
+            for s in source
+                .source_orcid
+                .iter_mut()
+                .chain(&mut source.source_client_id)
+            {
+                s.host = Some("sandbox.orcid.org".to_string());
+            }
+        }
+        let works: Vec<&OrcidWorkDetail> = file.records.iter().collect();
+        assert_eq!(
+            profile_uri(&works).as_deref(),
+            Some("https://sandbox.orcid.org/0000-0002-1825-0097")
+        );
+        let feed = to_atom(
+            &works,
+            "Publications",
+            "https://example.org/",
+            "J. Carberry",
+        );
+        assert!(feed.contains("<id>https://sandbox.orcid.org/0000-0002-1825-0097/work/1001</id>"));
+        assert!(!feed.contains("https://orcid.org/"), "{feed}");
+    }
+}
@@ -1,36 +1,77 @@
-use anyhow::{Context, Result};
-use clap::Parser;
-use futures::stream::{self, StreamExt, TryStreamExt};
-use governor::{Quota, RateLimiter, clock::DefaultClock, state::InMemoryState, state::NotKeyed};
-use std::{collections::HashMap, num::NonZeroU32, path::PathBuf, sync::Arc};
+use anyhow::{Context, Result, bail};
+use clap::{ArgAction, Parser, Subcommand};
+use governor::Quota;
+use std::{
+    collections::{BTreeMap, HashSet},
+    num::NonZeroU32,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
 
-use tracing::{Instrument, info, info_span, warn};
+use tracing::{error, info, warn};
 
-use orcid_works_model::{OrcidWorkDetail, OrcidWorkDetailFile, OrcidWorks};
+use orcid_works_model::{OrcidCombinedFile, OrcidId, OrcidPerson, OrcidRecordFile};
 
 mod api;
 mod compare;
+mod diff;
+mod export;
+mod feed;
 mod io;
-use api::{build_client, fetch_work_detail, fetch_works};
-use compare::{added_putcodes, deleted_putcodes, diff_putcodes, kept_putcodes, updated_putcodes};
-use io::{read_work_details_json, write_pretty_json};
+mod render;
+mod report;
+mod section;
+mod sync;
+use api::{Limiter, RetryPolicy, build_client, fetch_person, parse_api_base};
+use compare::Diff;
+use diff::{DiffArgs, run_diff};
+use export::{ExportArgs, run_export};
+use feed::{FeedArgs, run_feed};
+use io::{
+    JsonStyle, is_ndjson, read_id_file, read_json_or_default, read_record_dir, read_record_file,
+    write_json, write_pretty_json, write_record_dir, write_record_file,
+};
+use render::{RenderArgs, run_render};
+use report::{
+    Change, DryRunFormat, RunReport, SectionChanges, epoch_millis, print_dry_run, write_report,
+};
+use section::{Affiliations, Fundings, PeerReviews, Section, SectionKind, Works};
+use sync::{SummaryCache, SyncContext, SyncOutcome, sync_section};
 
 // Environment Constants
 const APP_NAME: &str = env!("CARGO_PKG_NAME");
 const APP_VERSION: &str = env!("CARGO_PKG_VERSION");
 const REPO_URL: &str = env!("CARGO_REPOSITORY_URL");
 
-// Parallel fetch with rate limit
-type Limiter = RateLimiter<NotKeyed, InMemoryState, DefaultClock>;
+// Exit codes
+// Processing stopped; outputs written for earlier iDs and sections are kept
+const EXIT_FAILURE: i32 = 1;
+// Output written, but some records could not be fetched (`--keep-going`)
+const EXIT_PARTIAL: i32 = 3;
+
+// Outcome of a successful run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RunStatus {
+    Complete,
+    Partial,
+}
 
-async fn guarded_fetch<T, F>(l: &Limiter, fut: F) -> Result<T>
-where
-    F: Future<Output = Result<T>>,
-{
-    l.until_ready().await;
-    fut.await
+// Output layout of a sync
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Layout {
+    // One records file per iD
+    PerId,
+    // One file keyed by iD (`--combined`)
+    Combined,
+    // One directory per iD with a file per record (`--per-record`)
+    PerRecord,
 }
 
+// Placeholders in `--out` replaced by each ORCID iD / record section
+const ID_PLACEHOLDER: &str = "{id}";
+const SECTION_PLACEHOLDER: &str = "{section}";
+
 // Build User-Agent string
 fn build_user_agent(note: Option<String>) -> String {
     let base = format!("{APP_NAME}/{APP_VERSION} (+{REPO_URL})");
@@ -55,22 +96,76 @@ fn ranged_usize_parser<const MIN: usize, const MAX: usize>(s: &str) -> Result<us
 #[command(
     author,
     version,
-    about = "Fetch all WorkDetails for a given ORCID iD (ORCID API v3.0)",
+    about = "Fetch all WorkDetails (and other record sections) for a given ORCID iD (ORCID API v3.0)",
     bin_name = "orcid-works-cli",
-    after_help = "Disclaimer: This is a third-party tool and not endorsed by ORCID."
+    after_help = "Disclaimer: This is a third-party tool and not endorsed by ORCID.",
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
 )]
 struct Cli {
-    #[arg(short = 'i', long, help = "ORCID iD (xxxx-xxxx-xxxx-xxxx).")]
-    id: String,
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[arg(
+        short = 'i',
+        long,
+        action = ArgAction::Append,
+        required_unless_present = "id_file",
+        help = "ORCID iD (xxxx-xxxx-xxxx-xxxX or https://orcid.org/...); repeat for several iDs."
+    )]
+    id: Vec<OrcidId>,
+
+    #[arg(
+        long = "id-file",
+        help = "File with one ORCID iD per line (blank lines and `#` comments are ignored)"
+    )]
+    id_file: Option<PathBuf>,
 
     #[arg(
         short = 'o',
         long,
         default_value = "./output.json",
-        help = "Output path to the JSON file; Parent dirs are created if absent."
+        help = "Output path to the JSON file; Parent dirs are created if absent. `{id}` is replaced by each iD (e.g. out/{id}.json). A .ndjson/.jsonl extension writes one record per line."
     )]
     out: PathBuf,
 
+    #[arg(
+        short = 's',
+        long = "section",
+        value_enum,
+        action = ArgAction::Append,
+        default_values_t = [SectionKind::Works],
+        help = "Record section to download; repeat for several sections (use `{section}` in --out)"
+    )]
+    section: Vec<SectionKind>,
+
+    #[arg(
+        long = "person-out",
+        help = "Also fetch person data (name, biography, keywords, ...) into this JSON file; `{id}` is replaced by each iD"
+    )]
+    person_out: Option<PathBuf>,
+
+    #[arg(
+        long = "combined",
+        default_value_t = false,
+        help = "Write all iDs into the single file --out, keyed by iD"
+    )]
+    combined: bool,
+
+    #[arg(
+        long = "per-record",
+        default_value_t = false,
+        help = "Treat --out as a directory holding one <put-code>.json per record and an index.json; only added or updated records are rewritten"
+    )]
+    per_record: bool,
+
+    #[arg(
+        long = "canonical",
+        default_value_t = false,
+        help = "Write canonical JSON: sorted keys, two-space indent, NFC-normalized strings, trailing newline"
+    )]
+    canonical: bool,
+
     #[arg(
         long = "concurrency",
         default_value_t = 8,
@@ -87,6 +182,37 @@ struct Cli {
     )]
     rate_limit: u32,
 
+    #[arg(
+        long = "retry-max-attempts",
+        default_value_t = 4,
+        value_parser = clap::value_parser!(u32).range(1..=10),
+        help = "Attempts per request on HTTP 429/5xx, timeouts and connection errors (1–10)"
+    )]
+    retry_max_attempts: u32,
+
+    #[arg(
+        long = "retry-base-delay-ms",
+        default_value_t = 500,
+        help = "Initial backoff in milliseconds; doubled on each retry unless Retry-After is sent"
+    )]
+    retry_base_delay_ms: u64,
+
+    #[arg(
+        long = "retry-jitter-ms",
+        default_value_t = 250,
+        help = "Maximum random jitter in milliseconds added to each backoff"
+    )]
+    retry_jitter_ms: u64,
+
+    #[arg(
+        long = "api-base",
+        env = "ORCID_API_BASE",
+        default_value = "production",
+        value_parser = parse_api_base,
+        help = "ORCID Public API base: `production`, `sandbox`, or a URL (e.g. http://localhost:8080/v3.0)"
+    )]
+    api_base: String,
+
     #[arg(
         long = "user-agent-note",
         help = "Extra text appended to the built-in User-Agent string [default: None]"
@@ -99,22 +225,98 @@ struct Cli {
         help = "Ignore diff and refetch every work-detail entry"
     )]
     force_fetch: bool,
+
+    #[arg(
+        long = "keep-going",
+        default_value_t = false,
+        help = "Write the output even if some work details fail; failed works keep their previous record and the exit code is 3"
+    )]
+    keep_going: bool,
+
+    #[arg(
+        long = "verify",
+        default_value_t = false,
+        help = "Also refetch kept works whose stored title, type, external-ids or publication-date disagree with the summary"
+    )]
+    verify: bool,
+
+    #[arg(
+        long = "dry-run",
+        default_value_t = false,
+        help = "Only fetch summaries and print the added/updated/kept/deleted put-codes; nothing is written"
+    )]
+    dry_run: bool,
+
+    #[arg(
+        long = "dry-run-format",
+        value_enum,
+        default_value_t = DryRunFormat::Text,
+        requires = "dry_run",
+        help = "Output format of --dry-run"
+    )]
+    dry_run_format: DryRunFormat,
+
+    #[arg(
+        long = "report",
+        help = "Write a JSON report of the put-codes added/updated/kept/deleted per iD and section, with run metadata"
+    )]
+    report: Option<PathBuf>,
+}
+
+// Offline tools; without a subcommand the CLI syncs the given iDs
+#[derive(Subcommand)]
+enum Command {
+    #[command(about = "Show works added, removed or modified between two work-details files")]
+    Diff(DiffArgs),
+    #[command(about = "Convert a work-details file into a citation format")]
+    Export(ExportArgs),
+    #[command(about = "Render a work-details file as an HTML or Markdown publication list")]
+    Render(RenderArgs),
+    #[command(about = "Write an Atom or RSS feed of the most recently added works")]
+    Feed(FeedArgs),
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    tracing_subscriber::fmt().init();
-
-    if let Err(err) = run().await {
-        tracing::error!(err = %err, "fatal error; exiting:");
-        std::process::exit(1);
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .init();
+
+    match run().await {
+        Ok(RunStatus::Complete) => {}
+        Ok(RunStatus::Partial) => std::process::exit(EXIT_PARTIAL),
+        Err(err) => {
+            tracing::error!(err = %err, "fatal error; exiting:");
+            std::process::exit(EXIT_FAILURE);
+        }
     }
 
     Ok(())
 }
 
-async fn run() -> anyhow::Result<()> {
+async fn run() -> anyhow::Result<RunStatus> {
     let cli = Cli::parse();
+    let started_at = SystemTime::now();
+
+    match &cli.command {
+        Some(Command::Diff(args)) => {
+            run_diff(args)?;
+            return Ok(RunStatus::Complete);
+        }
+        Some(Command::Export(args)) => {
+            run_export(args)?;
+            return Ok(RunStatus::Complete);
+        }
+        Some(Command::Render(args)) => {
+            run_render(args)?;
+            return Ok(RunStatus::Complete);
+        }
+        Some(Command::Feed(args)) => {
+            run_feed(args)?;
+            return Ok(RunStatus::Complete);
+        }
+        None => {}
+    }
 
     // Concurrency & Rate Limit check
     if cli.concurrency > cli.rate_limit.try_into().unwrap() {
@@ -125,112 +327,564 @@ async fn run() -> anyhow::Result<()> {
         );
     }
 
+    // ORCID iDs from --id and --id-file, deduplicated in order
+    let mut ids: Vec<OrcidId> = cli.id.clone();
+    if let Some(path) = &cli.id_file {
+        ids.extend(read_id_file(path)?);
+    }
+    let mut seen = HashSet::new();
+    ids.retain(|id| seen.insert(id.clone()));
+    if ids.is_empty() {
+        bail!("no ORCID iD given");
+    }
+
+    // Record sections, deduplicated in order
+    let mut sections: Vec<SectionKind> = cli.section.clone();
+    let mut seen = HashSet::new();
+    sections.retain(|s| seen.insert(*s));
+
+    // Output layout
+    let out = cli.out.to_string_lossy();
+    if sections.len() > 1 && !out.contains(SECTION_PLACEHOLDER) {
+        bail!(
+            "{} sections given; use {SECTION_PLACEHOLDER} in --out (e.g. out/{SECTION_PLACEHOLDER}.json)",
+            sections.len()
+        );
+    }
+    check_id_layout(&cli.out, ids.len(), cli.combined, "--out")?;
+    if cli.combined && is_ndjson(&cli.out) {
+        bail!(
+            "--combined writes one JSON document keyed by iD; use {ID_PLACEHOLDER} in --out for NDJSON output"
+        );
+    }
+    if cli.per_record && cli.combined {
+        bail!("--per-record writes one directory per iD; it cannot be used with --combined");
+    }
+    if cli.per_record && is_ndjson(&cli.out) {
+        bail!("--per-record writes a directory of JSON files; --out must not end in .ndjson");
+    }
+    if let Some(person_out) = &cli.person_out {
+        check_id_layout(person_out, ids.len(), cli.combined, "--person-out")?;
+    }
+
     // HTTP client
     let ua = build_user_agent(cli.user_agent_note);
-    let client = build_client(&ua)?;
-    let id: Arc<str> = cli.id.clone().into();
-
-    // Rate limit
+    let retry = RetryPolicy {
+        max_attempts: cli.retry_max_attempts,
+        base_delay: Duration::from_millis(cli.retry_base_delay_ms),
+        jitter: Duration::from_millis(cli.retry_jitter_ms),
+    };
+    // Rate limit, shared by all iDs and applied to every attempt
     let limiter: Arc<Limiter> = Arc::new(Limiter::direct(Quota::per_second(
         NonZeroU32::new(cli.rate_limit).unwrap(),
     )));
+    let client = build_client(&ua, &cli.api_base, retry, limiter)?;
+    info!(api_base = client.base(), "using ORCID API base");
+
+    let ctx = SyncContext {
+        client,
+        concurrency: cli.concurrency,
+        force_fetch: cli.force_fetch,
+        keep_going: cli.keep_going,
+        dry_run: cli.dry_run,
+        verify: cli.verify,
+        json: if cli.canonical {
+            JsonStyle::Canonical
+        } else {
+            JsonStyle::Pretty
+        },
+    };
+
+    let layout = if cli.combined {
+        Layout::Combined
+    } else if cli.per_record {
+        Layout::PerRecord
+    } else {
+        Layout::PerId
+    };
+
+    let mut partial = false;
+    let mut changes: Vec<SectionChanges> = Vec::new();
+    for kind in sections {
+        let out = PathBuf::from(
+            cli.out
+                .to_string_lossy()
+                .replace(SECTION_PLACEHOLDER, kind.name()),
+        );
+        partial |= match kind {
+            SectionKind::Works => {
+                sync_outputs(&ctx, &Works, &ids, &out, layout, &mut changes).await?
+            }
+            SectionKind::Fundings => {
+                sync_outputs(&ctx, &Fundings, &ids, &out, layout, &mut changes).await?
+            }
+            SectionKind::PeerReviews => {
+                sync_outputs(&ctx, &PeerReviews, &ids, &out, layout, &mut changes).await?
+            }
+            SectionKind::Employments
+            | SectionKind::Educations
+            | SectionKind::Qualifications
+            | SectionKind::InvitedPositions
+            | SectionKind::Distinctions
+            | SectionKind::Memberships
+            | SectionKind::Services => {
+                let section = Affiliations(kind);
+                sync_outputs(&ctx, &section, &ids, &out, layout, &mut changes).await?
+            }
+        };
+    }
 
-    // Open the existing work details JSON
-    info!(
-        path = &cli.out.display().to_string(),
-        "opening the existing work-details JSON"
-    );
-    let existing: OrcidWorkDetailFile = read_work_details_json(&cli.out).with_context(|| {
-        format!(
-            "open the existing work-details JSON from {}",
-            &cli.out.display()
-        )
-    })?;
-    let existing_map: HashMap<u64, OrcidWorkDetail> = existing
-        .records
-        .into_iter()
-        .map(|d| (d.summary.put_code, d))
+    if cli.dry_run {
+        if cli.person_out.is_some() {
+            info!("dry run - person data not fetched");
+        }
+        print_dry_run(&changes, cli.dry_run_format)?;
+    } else if let Some(person_out) = &cli.person_out {
+        partial |= if cli.combined {
+            sync_person_combined(&ctx, &ids, person_out).await?
+        } else {
+            sync_person_each(&ctx, &ids, person_out).await?
+        };
+    }
+
+    if let Some(path) = &cli.report {
+        let report = RunReport {
+            ids: &ids,
+            started_at: epoch_millis(started_at),
+            finished_at: epoch_millis(SystemTime::now()),
+            requests: ctx.client.request_count(),
+            dry_run: cli.dry_run,
+            status: if partial { "partial" } else { "complete" },
+            sections: &changes,
+        };
+        info!(path = path.display().to_string(), "writing change report");
+        write_report(path, &report)?;
+    }
+
+    if cli.dry_run {
+        info!("dry run finished; outputs left untouched");
+        return Ok(RunStatus::Complete);
+    }
+
+    if partial {
+        warn!("finished with failures; see the warnings above");
+        return Ok(RunStatus::Partial);
+    }
+
+    info!("finished successfully");
+
+    Ok(RunStatus::Complete)
+}
+
+// Reject output paths that would make several iDs overwrite one file
+fn check_id_layout(path: &Path, id_count: usize, combined: bool, flag: &str) -> Result<()> {
+    let templated = path.to_string_lossy().contains(ID_PLACEHOLDER);
+    if combined && templated {
+        bail!("--combined writes a single file; remove {ID_PLACEHOLDER} from {flag}");
+    }
+    if id_count > 1 && !combined && !templated {
+        bail!(
+            "{id_count} iDs given; use {ID_PLACEHOLDER} in {flag} (e.g. out/{ID_PLACEHOLDER}.json) or --combined"
+        );
+    }
+    Ok(())
+}
+
+// Output path for one iD
+fn output_path(out: &Path, id: &OrcidId) -> PathBuf {
+    PathBuf::from(out.to_string_lossy().replace(ID_PLACEHOLDER, id.as_str()))
+}
+
+// Validators of the summary lists behind one output file, keyed by iD
+type CacheFile = BTreeMap<OrcidId, SummaryCache>;
+
+// Sidecar next to an output file or directory, named after all of it so that
+// `works.json`, `works.ndjson` and `works/` do not share one:
+// works.json.meta.json, works.ndjson.meta.json, works.meta.json
+fn cache_path(out: &Path) -> PathBuf {
+    let mut name = out.file_name().unwrap_or_default().to_os_string();
+    name.push(".meta.json");
+    out.with_file_name(name)
+}
+
+fn read_cache(out: &Path) -> Result<CacheFile> {
+    let path = cache_path(out);
+    read_json_or_default(&path)
+        .with_context(|| format!("open the cache metadata JSON from {}", path.display()))
+}
+
+fn write_cache(out: &Path, cache: &CacheFile) -> Result<()> {
+    let path = cache_path(out);
+    write_pretty_json(&path, cache)
+        .with_context(|| format!("write cache metadata JSON to {}", path.display()))
+}
+
+// Record the validators of one iD; returns whether the cache changed
+fn update_cache(cache: &mut CacheFile, id: &OrcidId, entry: Option<SummaryCache>) -> bool {
+    match entry {
+        Some(entry) => cache.insert(id.clone(), entry.clone()) != Some(entry),
+        None => cache.remove(id).is_some(),
+    }
+}
+
+// Sync one section for all iDs, collecting the diff of each iD into `changes`;
+// returns whether any iD was only partially synced
+async fn sync_outputs<S: Section>(
+    ctx: &SyncContext,
+    section: &S,
+    ids: &[OrcidId],
+    out: &Path,
+    layout: Layout,
+    changes: &mut Vec<SectionChanges>,
+) -> Result<bool> {
+    match layout {
+        Layout::Combined => sync_combined(ctx, section, ids, out, changes).await,
+        Layout::PerId => sync_each(ctx, section, ids, out, false, changes).await,
+        Layout::PerRecord => sync_each(ctx, section, ids, out, true, changes).await,
+    }
+}
+
+// Files a per-record sync touches: (records to rewrite, records to delete).
+// Failed records keep their previous file.
+fn touched_records(changes: &[Change], failed: &[u64]) -> (HashSet<u64>, Vec<u64>) {
+    let rewrite = changes
+        .iter()
+        .filter(|c| matches!(c.diff, Diff::Added | Diff::Updated) && !failed.contains(&c.put_code))
+        .map(|c| c.put_code)
+        .collect();
+    let delete = changes
+        .iter()
+        .filter(|c| c.diff == Diff::Deleted)
+        .map(|c| c.put_code)
         .collect();
+    (rewrite, delete)
+}
+
+// Sync one iD; in keep-going mode a failed iD is logged and yields `None`
+async fn sync_one<S: Section>(
+    ctx: &SyncContext,
+    section: &S,
+    id: &OrcidId,
+    existing: OrcidRecordFile<S::Record>,
+    cache: Option<&SummaryCache>,
+) -> Result<Option<SyncOutcome<S::Record>>> {
+    match sync_section(ctx, section, id, existing.records, cache).await {
+        Ok(outcome) => {
+            if !outcome.failed.is_empty() {
+                warn!(
+                    %id,
+                    section = section.name(),
+                    failed = outcome.failed.len(),
+                    putcodes = ?outcome.failed,
+                    "some records failed; previous versions kept for them"
+                );
+            }
+            Ok(Some(outcome))
+        }
+        Err(e) if ctx.keep_going => {
+            error!(
+                %id,
+                section = section.name(),
+                err = format!("{e:#}"),
+                "failed to sync iD; previous records kept"
+            );
+            Ok(None)
+        }
+        Err(e) => Err(e).with_context(|| format!("sync {} of ORCID iD {id}", section.name())),
+    }
+}
+
+// One output file per iD; returns whether any iD was only partially synced
+async fn sync_each<S: Section>(
+    ctx: &SyncContext,
+    section: &S,
+    ids: &[OrcidId],
+    out: &Path,
+    per_record: bool,
+    changes: &mut Vec<SectionChanges>,
+) -> Result<bool> {
+    let mut partial = false;
+
+    for id in ids {
+        let path = output_path(out, id);
+
+        // Open the existing records JSON
+        info!(
+            path = &path.display().to_string(),
+            section = section.name(),
+            "opening the existing records JSON"
+        );
+        let existing: OrcidRecordFile<S::Record> = if per_record {
+            read_record_dir(&path)
+        } else {
+            read_record_file(&path)
+        }
+        .with_context(|| format!("open the existing records JSON from {}", &path.display()))?;
+
+        let mut cache = read_cache(&path)?;
+
+        let Some(outcome) = sync_one(ctx, section, id, existing, cache.get(id)).await? else {
+            partial = true;
+            if !ctx.dry_run && update_cache(&mut cache, id, None) {
+                write_cache(&path, &cache)?;
+            }
+            continue;
+        };
+        partial |= !outcome.failed.is_empty();
+        let touched = per_record.then(|| {
+            touched_records(
+                outcome.changes.as_deref().unwrap_or_default(),
+                &outcome.failed,
+            )
+        });
+        changes.push(SectionChanges {
+            id: id.clone(),
+            section: section.name(),
+            changes: outcome.changes,
+            failed: outcome.failed,
+        });
+        if ctx.dry_run {
+            continue;
+        }
+
+        // Write JSON
+        if let Some(records) = outcome.records {
+            info!(
+                path = path.display().to_string(),
+                section = section.name(),
+                "writing records JSON"
+            );
+            match touched {
+                Some((rewrite, delete)) => {
+                    write_record_dir(&path, &records, &rewrite, &delete, ctx.json)
+                }
+                None => write_record_file(&path, &OrcidRecordFile { records }, ctx.json),
+            }
+            .with_context(|| format!("write records JSON to {}", path.display()))?;
+        }
 
-    // Fetch works summaries
-    info!(id = &cli.id, "fetching work summaries");
-    let works: OrcidWorks = fetch_works(&client, &cli.id)
-        .in_current_span()
-        .await
-        .with_context(|| "update cache: fetch work summaries")?;
+        // Write the cache after the records it describes
+        if update_cache(&mut cache, id, outcome.cache) {
+            write_cache(&path, &cache)?;
+        }
+    }
 
-    // Detect changes
-    let diff_map = diff_putcodes(&existing_map, &works, cli.force_fetch);
-    let added = added_putcodes(&diff_map);
-    let updated = updated_putcodes(&diff_map);
-    let kept = kept_putcodes(&diff_map);
-    let deleted = deleted_putcodes(&diff_map);
+    Ok(partial)
+}
 
+// A single output file keyed by iD; returns whether any iD was only partially synced
+async fn sync_combined<S: Section>(
+    ctx: &SyncContext,
+    section: &S,
+    ids: &[OrcidId],
+    out: &Path,
+    changes: &mut Vec<SectionChanges>,
+) -> Result<bool> {
+    let mut partial = false;
+
+    // Open the existing combined JSON
     info!(
-        added = added.len(),
-        updated = updated.len(),
-        deleted = deleted.len(),
-        "diff stats"
+        path = &out.display().to_string(),
+        section = section.name(),
+        "opening the existing combined records JSON"
     );
+    let mut combined: OrcidCombinedFile<S::Record> =
+        read_json_or_default(out).with_context(|| {
+            format!(
+                "open the existing combined records JSON from {}",
+                out.display()
+            )
+        })?;
+
+    let mut cache = read_cache(out)?;
+
+    // Drop iDs that are no longer requested
+    let before = combined.ids.len();
+    combined.ids.retain(|id, _| {
+        let keep = ids.contains(id);
+        if !keep {
+            info!(%id, "removing iD that is no longer requested");
+        }
+        keep
+    });
+    let mut changed = combined.ids.len() != before;
+    let cache_before = cache.len();
+    cache.retain(|id, _| ids.contains(id));
+    let mut cache_changed = cache.len() != cache_before;
+
+    for id in ids {
+        let existing = match combined.ids.get(id) {
+            Some(file) => file.clone(),
+            None => {
+                changed = true;
+                OrcidRecordFile::default()
+            }
+        };
+
+        let Some(outcome) = sync_one(ctx, section, id, existing.clone(), cache.get(id)).await?
+        else {
+            partial = true;
+            combined.ids.insert(id.clone(), existing);
+            cache_changed |= update_cache(&mut cache, id, None);
+            continue;
+        };
+        partial |= !outcome.failed.is_empty();
+        cache_changed |= update_cache(&mut cache, id, outcome.cache);
+        changes.push(SectionChanges {
+            id: id.clone(),
+            section: section.name(),
+            changes: outcome.changes,
+            failed: outcome.failed,
+        });
+
+        match outcome.records {
+            Some(records) => {
+                changed = true;
+                combined.ids.insert(id.clone(), OrcidRecordFile { records });
+            }
+            None => {
+                combined.ids.insert(id.clone(), existing);
+            }
+        }
+    }
 
-    // Exit if no changes detected
-    let to_fetch: Vec<u64> = added.into_iter().chain(updated.into_iter()).collect();
+    if ctx.dry_run {
+        return Ok(partial);
+    }
 
-    if to_fetch.len() + deleted.len() == 0 {
+    // Write JSON
+    if changed {
         info!(
-            to_fetch = to_fetch.len(),
-            deleted = deleted.len(),
-            "no changes detected - skip fetch & rewrite"
+            path = out.display().to_string(),
+            section = section.name(),
+            "writing combined records JSON"
         );
-        return Ok(());
-    }
-
-    // Parallel fetch work details
-    info!(id = &cli.id, "fetching work details");
-    let batch_span = info_span!("fetch_work_details_batch",
-                            id    = %*id,
-                            total = to_fetch.len());
-    let fetched: Vec<OrcidWorkDetail> = stream::iter(to_fetch)
-        .map(|pc| {
-            let task_span = info_span!("work_detail_task", %pc);
-            let limiter = limiter.clone();
-            let client = client.clone();
-            let id = id.clone();
-
-            async move {
-                guarded_fetch(
-                    &limiter,
-                    fetch_work_detail(&client, &id, pc).in_current_span(),
-                )
-                .await
+        write_json(out, &combined, ctx.json)
+            .with_context(|| format!("write combined records JSON to {}", out.display()))?;
+    } else {
+        info!("no changes detected in any iD - skip rewrite");
+    }
+    if cache_changed {
+        write_cache(out, &cache)?;
+    }
+
+    Ok(partial)
+}
+
+// Fetch person data; in keep-going mode a failed iD is logged and yields `None`
+async fn fetch_person_guarded(ctx: &SyncContext, id: &OrcidId) -> Result<Option<OrcidPerson>> {
+    info!(%id, "fetching person data");
+    match fetch_person(&ctx.client, id).await {
+        Ok(person) => Ok(Some(person)),
+        Err(e) if ctx.keep_going => {
+            error!(%id, err = format!("{e:#}"), "failed to fetch person data; previous file kept");
+            Ok(None)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+// One person JSON per iD, rewritten only when its content changed
+async fn sync_person_each(ctx: &SyncContext, ids: &[OrcidId], out: &Path) -> Result<bool> {
+    let mut partial = false;
+
+    for id in ids {
+        let path = output_path(out, id);
+        let existing: Option<OrcidPerson> = read_json_or_default(&path)
+            .with_context(|| format!("open the existing person JSON from {}", path.display()))?;
+
+        let Some(person) = fetch_person_guarded(ctx, id).await? else {
+            partial = true;
+            continue;
+        };
+
+        if existing.as_ref() == Some(&person) {
+            info!(%id, "person data unchanged - skip rewrite");
+            continue;
+        }
+
+        info!(path = path.display().to_string(), "writing person JSON");
+        write_json(&path, &person, ctx.json)
+            .with_context(|| format!("write person JSON to {}", path.display()))?;
+    }
+
+    Ok(partial)
+}
+
+// A single person JSON keyed by iD, rewritten only when its content changed
+async fn sync_person_combined(ctx: &SyncContext, ids: &[OrcidId], out: &Path) -> Result<bool> {
+    let mut partial = false;
+
+    let existing: BTreeMap<OrcidId, OrcidPerson> = read_json_or_default(out)
+        .with_context(|| format!("open the existing person JSON from {}", out.display()))?;
+    let mut combined: BTreeMap<OrcidId, OrcidPerson> = BTreeMap::new();
+
+    for id in ids {
+        match fetch_person_guarded(ctx, id).await? {
+            Some(person) => {
+                combined.insert(id.clone(), person);
             }
-            .instrument(task_span)
-        })
-        .buffer_unordered(cli.concurrency)
-        .try_collect::<Vec<_>>()
-        .instrument(batch_span)
-        .await
-        .with_context(|| format!("batch fetch for ORCID iD {id}"))?;
-
-    // Merge
-    let mut merged: Vec<OrcidWorkDetail> = kept
-        .into_iter()
-        .filter_map(|pc| existing_map.get(&pc).cloned())
-        .chain(fetched.into_iter())
-        .collect();
+            None => {
+                partial = true;
+                if let Some(person) = existing.get(id) {
+                    combined.insert(id.clone(), person.clone());
+                }
+            }
+        }
+    }
 
-    merged.sort_by_key(|d| d.summary.put_code);
+    if combined == existing {
+        info!("person data unchanged - skip rewrite");
+        return Ok(partial);
+    }
 
-    // Write JSON
     info!(
-        path = cli.out.display().to_string(),
-        "writing work-details JSON"
+        path = out.display().to_string(),
+        "writing combined person JSON"
     );
-    let out_json = OrcidWorkDetailFile { records: merged };
-    write_pretty_json(&cli.out, &out_json)
-        .with_context(|| format!("write work-details JSON to {}", cli.out.display()))?;
+    write_json(out, &combined, ctx.json)
+        .with_context(|| format!("write combined person JSON to {}", out.display()))?;
 
-    info!("finished successfully");
+    Ok(partial)
+}
 
-    Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sidecars_are_named_after_the_whole_output_name() {
+        let sidecar = |out: &str| cache_path(Path::new(out));
+        assert_eq!(
+            sidecar("data/works.json"),
+            Path::new("data/works.json.meta.json")
+        );
+        assert_eq!(
+            sidecar("data/works.ndjson"),
+            Path::new("data/works.ndjson.meta.json")
+        );
+        assert_eq!(sidecar("data/works"), Path::new("data/works.meta.json"));
+        assert_eq!(sidecar("data/works/"), Path::new("data/works.meta.json"));
+    }
+
+    #[test]
+    fn written_person_reads_back_unchanged() {
+        let person = OrcidPerson::from_reader(
+            include_str!("../../orcid-works-model/tests/fixtures/person.json").as_bytes(),
+        )
+        .unwrap();
+        let dir = tempfile::tempdir().expect("temp dir");
+        let path = dir.path().join("person.json");
+
+        // No previous file means no previous person
+        let missing: Option<OrcidPerson> = read_json_or_default(&path).unwrap();
+        assert_eq!(missing, None);
+
+        // `--person` skips the rewrite when the fetched person equals the file
+        for style in [JsonStyle::Pretty, JsonStyle::Canonical] {
+            write_json(&path, &person, style).unwrap();
+            let existing: Option<OrcidPerson> = read_json_or_default(&path).unwrap();
+            assert_eq!(existing.as_ref(), Some(&person));
+        }
+    }
 }
@@ -0,0 +1,453 @@
+use anyhow::{Context, Result, anyhow};
+use clap::{Args, ValueEnum};
+use serde::Serialize;
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, HashMap},
+    io::Write,
+    path::PathBuf,
+};
+use tera::Tera;
+
+use orcid_works_model::{OrcidId, OrcidRecord, OrcidWorkDetail, OrcidWorkDetailFile};
+
+use crate::export::{
+    contributors, credit_name, day, doi_url, journal, month, owner_id, to_json_ld, url, year,
+};
+use crate::io::{read_required_records, write_text};
+
+const HTML_TEMPLATE: &str = include_str!("../templates/list.html");
+const MARKDOWN_TEMPLATE: &str = include_str!("../templates/list.md");
+
+// Markup of the built-in templates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub(crate) enum RenderFormat {
+    Html,
+    Markdown,
+}
+
+// Heading the works are grouped under
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub(crate) enum GroupBy {
+    Year,
+    Type,
+}
+
+// `render` subcommand: publication list for a static site
+#[derive(Args)]
+pub(crate) struct RenderArgs {
+    #[arg(help = "Work-details JSON written by a sync")]
+    input: PathBuf,
+
+    #[arg(
+        short = 'f',
+        long = "format",
+        value_enum,
+        default_value_t = RenderFormat::Html,
+        conflicts_with = "template",
+        help = "Markup of the built-in template"
+    )]
+    format: RenderFormat,
+
+    #[arg(
+        long = "group-by",
+        value_enum,
+        default_value_t = GroupBy::Year,
+        help = "Group works by publication year (newest first) or by work type"
+    )]
+    group_by: GroupBy,
+
+    #[arg(
+        long = "owner",
+        help = "Highlight contributors with this ORCID iD [default: the iD in each work's path]"
+    )]
+    owner: Option<OrcidId>,
+
+    #[arg(
+        long = "template",
+        help = "Tera template used instead of the built-in one; HTML-escaped if the file name ends in .html/.htm/.xml"
+    )]
+    template: Option<PathBuf>,
+
+    #[arg(
+        short = 'o',
+        long = "out",
+        help = "Output file (parent dirs are created); printed to stdout if omitted"
+    )]
+    out: Option<PathBuf>,
+}
+
+#[derive(Serialize)]
+struct ListContributor {
+    name: String,
+    orcid: Option<String>,
+    role: Option<String>,
+    // Contributor is the profile owner
+    owner: bool,
+}
+
+#[derive(Serialize)]
+struct ListWork {
+    put_code: u64,
+    title: String,
+    subtitle: Option<String>,
+    r#type: String,
+    type_label: String,
+    year: Option<String>,
+    journal: Option<String>,
+    doi: Option<String>,
+    doi_url: Option<String>,
+    url: Option<String>,
+    description: Option<String>,
+    contributors: Vec<ListContributor>,
+    #[serde(skip)]
+    sort_date: (u32, u32, u32),
+}
+
+#[derive(Serialize)]
+struct ListGroup {
+    // Year or work type; empty for undated works
+    key: String,
+    label: String,
+    works: Vec<ListWork>,
+}
+
+// Context passed to the template
+#[derive(Serialize)]
+struct ListContext {
+    group_by: &'static str,
+    owner: Option<String>,
+    total: usize,
+    groups: Vec<ListGroup>,
+    // schema.org JSON-LD of all works, safe inside a <script> element
+    json_ld: String,
+}
+
+// "journal-article" -> "Journal article"
+fn type_label(work_type: &str) -> String {
+    let label = work_type.replace(['-', '_'], " ");
+    let mut chars = label.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => label,
+    }
+}
+
+// Only http(s) URLs become links; the URL comes from the work's source, and
+// escaping does not stop e.g. a `javascript:` URL in an href
+fn web_url(url: &str) -> bool {
+    ["http://", "https://"].iter().any(|scheme| {
+        url.get(..scheme.len())
+            .is_some_and(|s| s.eq_ignore_ascii_case(scheme))
+    })
+}
+
+fn list_work(work: &OrcidWorkDetail, owner: Option<&str>) -> ListWork {
+    let owner = owner.or_else(|| Some(owner_id(work)).filter(|id| !id.is_empty()));
+    let contributors = ["author", "editor"]
+        .into_iter()
+        .flat_map(|role| contributors(work, role).map(move |c| (role, c)))
+        .filter_map(|(role, c)| {
+            let name = credit_name(c)?;
+            let orcid = c.contributor_orcid.as_ref().and_then(|o| o.path.clone());
+            Some(ListContributor {
+                name: name.to_string(),
+                owner: owner.is_some() && orcid.as_deref() == owner,
+                orcid,
+                role: Some(role.to_string()),
+            })
+        })
+        .collect();
+    let year = year(work);
+    let doi = work.doi();
+    ListWork {
+        put_code: work.put_code(),
+        title: work.summary.title.title.value.clone(),
+        subtitle: work
+            .summary
+            .title
+            .subtitle
+            .as_ref()
+            .map(|s| s.value.clone()),
+        r#type: work.summary.r#type.clone(),
+        type_label: type_label(&work.summary.r#type),
+        year: year.map(str::to_string),
+        journal: journal(work).map(str::to_string),
+        doi: doi.map(str::to_string),
+        doi_url: doi.map(doi_url),
+        url: url(work).filter(|u| web_url(u)).map(str::to_string),
+        description: work.short_description.clone(),
+        contributors,
+        sort_date: (
+            year.and_then(|y| y.parse().ok()).unwrap_or_default(),
+            month(work).unwrap_or_default(),
+            day(work).unwrap_or_default(),
+        ),
+    }
+}
+
+// Groups in display order: years newest first with undated works last, or
+// work types by label; works within a group newest first
+fn group(works: Vec<ListWork>, by: GroupBy) -> Vec<ListGroup> {
+    let mut groups: BTreeMap<(Reverse<u32>, String), Vec<ListWork>> = BTreeMap::new();
+    for work in works {
+        let key = match by {
+            GroupBy::Year => (Reverse(work.sort_date.0), String::new()),
+            GroupBy::Type => (Reverse(0), work.type_label.clone()),
+        };
+        groups.entry(key).or_default().push(work);
+    }
+
+    groups
+        .into_iter()
+        .map(|((Reverse(year), type_label), mut works)| {
+            works.sort_by_key(|w| (Reverse(w.sort_date), w.put_code));
+            let (key, label) = match by {
+                GroupBy::Year if year == 0 => (String::new(), "Undated".to_string()),
+                GroupBy::Year => (year.to_string(), year.to_string()),
+                GroupBy::Type => (works[0].r#type.clone(), type_label),
+            };
+            ListGroup { key, label, works }
+        })
+        .collect()
+}
+
+// Escape Markdown inline syntax in a string value (`md` filter)
+fn md_escape(value: &tera::Value, _: &HashMap<String, tera::Value>) -> tera::Result<tera::Value> {
+    let s = tera::try_get_value!("md", "value", String, value);
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '|' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\n' | '\r' => out.push(' '),
+            _ => out.push(c),
+        }
+    }
+    Ok(tera::Value::String(out))
+}
+
+// HTML escaping for autoescaped templates; unlike Tera's default it leaves `/`
+// alone so URLs stay readable
+fn escape_html(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+// Tera errors nest the useful part (line, unknown variable, ...) in their sources
+fn template_error(err: tera::Error) -> anyhow::Error {
+    let mut msg = err.to_string();
+    let mut source = std::error::Error::source(&err);
+    while let Some(s) = source {
+        msg.push_str(": ");
+        msg.push_str(&s.to_string());
+        source = s.source();
+    }
+    anyhow!(msg)
+}
+
+// Render the works with the named template; Tera autoescapes templates whose
+// name ends in .html/.htm/.xml
+fn render(
+    records: &[OrcidWorkDetail],
+    name: &str,
+    source: &str,
+    group_by: GroupBy,
+    owner: Option<&str>,
+) -> Result<String> {
+    let mut tera = Tera::default();
+    tera.register_filter("md", md_escape);
+    tera.set_escape_fn(escape_html);
+    tera.add_raw_template(name, source)
+        .map_err(template_error)?;
+
+    let works: Vec<ListWork> = records.iter().map(|w| list_work(w, owner)).collect();
+    let context = ListContext {
+        group_by: match group_by {
+            GroupBy::Year => "year",
+            GroupBy::Type => "type",
+        },
+        owner: owner.map(str::to_string),
+        total: works.len(),
+        groups: group(works, group_by),
+        json_ld: to_json_ld(records)?,
+    };
+    let context = tera::Context::from_serialize(&context).context("build template context")?;
+    tera.render(name, &context).map_err(template_error)
+}
+
+pub(crate) fn run_render(args: &RenderArgs) -> Result<()> {
+    let file: OrcidWorkDetailFile = read_required_records(&args.input)
+        .with_context(|| format!("open the work-details JSON from {}", args.input.display()))?;
+
+    let (name, source) = match &args.template {
+        Some(path) => {
+            let source = std::fs::read_to_string(path)
+                .with_context(|| format!("read template from {}", path.display()))?;
+            let name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            (name, source)
+        }
+        None => match args.format {
+            RenderFormat::Html => ("list.html".to_string(), HTML_TEMPLATE.to_string()),
+            RenderFormat::Markdown => ("list.md".to_string(), MARKDOWN_TEMPLATE.to_string()),
+        },
+    };
+    let owner = args.owner.as_ref().map(OrcidId::as_str);
+    let text = render(&file.records, &name, &source, args.group_by, owner)?;
+
+    match &args.out {
+        Some(path) => write_text(path, &text)
+            .with_context(|| format!("write publication list to {}", path.display())),
+        None => {
+            let mut stdout = std::io::stdout().lock();
+            stdout.write_all(text.as_bytes())?;
+            Ok(stdout.flush()?)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = include_str!("../tests/fixtures/works.json");
+
+    fn records() -> Vec<OrcidWorkDetail> {
+        let file: OrcidWorkDetailFile = serde_json::from_str(FIXTURE).expect("fixture parses");
+        file.records
+    }
+
+    fn groups(by: GroupBy) -> Vec<(String, Vec<u64>)> {
+        let works = records().iter().map(|w| list_work(w, None)).collect();
+        group(works, by)
+            .into_iter()
+            .map(|g| (g.label, g.works.iter().map(|w| w.put_code).collect()))
+            .collect()
+    }
+
+    fn html(records: &[OrcidWorkDetail], owner: Option<&str>) -> String {
+        render(records, "list.html", HTML_TEMPLATE, GroupBy::Year, owner).unwrap()
+    }
+
+    #[test]
+    fn groups_by_type_label_and_by_year_newest_first() {
+        let label = |l: &str, pcs: &[u64]| (l.to_string(), pcs.to_vec());
+        assert_eq!(
+            groups(GroupBy::Type),
+            [
+                label("Conference paper", &[1003]),
+                label("Edited book", &[1002]),
+                label("Journal article", &[1001]),
+                label("Software", &[1004]),
+            ]
+        );
+        assert_eq!(
+            groups(GroupBy::Year),
+            [
+                label("2021", &[1001]),
+                label("2020", &[1002]),
+                label("2019", &[1003]),
+                label("Undated", &[1004]),
+            ]
+        );
+    }
+
+    #[test]
+    fn works_within_a_group_are_newest_first() {
+        let mut records = records();
+        for w in &mut records {
+            w.summary.r#type = "journal-article".to_string();
+        }
+        let works = records.iter().map(|w| list_work(w, None)).collect();
+        let groups = group(works, GroupBy::Type);
+        let pcs: Vec<u64> = groups[0].works.iter().map(|w| w.put_code).collect();
+        assert_eq!(pcs, [1001, 1002, 1003, 1004]);
+    }
+
+    #[test]
+    fn highlights_the_profile_owner() {
+        let page = html(&records(), None);
+        assert!(page.contains("<strong>Josiah Carberry</strong>"), "{page}");
+        assert!(!page.contains("<strong>Müller, Jürgen</strong>"));
+
+        // --owner overrides the iD in the works' paths
+        let page = html(&records(), Some("0000-0001-5109-3700"));
+        assert!(!page.contains("<strong>Josiah Carberry</strong>"), "{page}");
+    }
+
+    #[test]
+    fn md_filter_escapes_markdown_syntax() {
+        let escaped = md_escape(
+            &tera::Value::from("*a* _b_ `c` [d](e) <f> #g h|i \\j\nk"),
+            &HashMap::new(),
+        )
+        .unwrap();
+        assert_eq!(
+            escaped,
+            tera::Value::from("\\*a\\* \\_b\\_ \\`c\\` \\[d\\](e) \\<f\\> \\#g h\\|i \\\\j k")
+        );
+        assert!(md_escape(&tera::Value::from(1), &HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn html_templates_escape_work_data() {
+        let mut records = records();
+        records[0].summary.title.title.value = "<script>alert('x')</script> & co".to_string();
+
+        let page = html(&records, None);
+        assert!(
+            page.contains("<cite>&lt;script&gt;alert(&#39;x&#39;)&lt;/script&gt; &amp; co"),
+            "{page}"
+        );
+        assert!(!page.contains("<script>alert"));
+
+        let custom =
+            "{% for g in groups %}{% for w in g.works %}{{ w.title }}\n{% endfor %}{% endfor %}";
+        let page = render(&records, "custom.html", custom, GroupBy::Year, None).unwrap();
+        assert!(page.starts_with("&lt;script&gt;alert(&#39;x&#39;)&lt;/script&gt; &amp; co\n"));
+
+        // Other custom templates are not HTML and stay unescaped
+        let page = render(&records, "custom.txt", custom, GroupBy::Year, None).unwrap();
+        assert!(
+            page.starts_with("<script>alert('x')</script> & co\n"),
+            "{page}"
+        );
+    }
+
+    #[test]
+    fn only_http_urls_are_linked() {
+        assert!(web_url("https://github.com/example/potfit"));
+        assert!(web_url("HTTP://example.org"));
+        assert!(!web_url("javascript:alert(1)"));
+        assert!(!web_url("data:text/html,x"));
+        assert!(!web_url("http"));
+
+        let mut records = records();
+        let page = html(&records, None);
+        assert!(page.contains(
+            "<a href=\"https://github.com/example/potfit\">https://github.com/example/potfit</a>"
+        ));
+
+        let software = records.iter_mut().find(|w| w.put_code() == 1004).unwrap();
+        software.url.as_mut().unwrap().value = "JavaScript:alert(document.cookie)".to_string();
+        let page = html(&records, None);
+        assert!(!page.contains("href=\"JavaScript:"), "{page}");
+        let md = render(&records, "list.md", MARKDOWN_TEMPLATE, GroupBy::Year, None).unwrap();
+        assert!(!md.contains("<JavaScript:"), "{md}");
+    }
+}
@@ -0,0 +1,206 @@
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::{
+    io::Write,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use orcid_works_model::{OrcidId, OrcidRecord};
+
+use crate::compare::Diff;
+use crate::io::write_pretty_json;
+
+// Output format of `--dry-run`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub(crate) enum DryRunFormat {
+    Text,
+    Json,
+}
+
+// One put-code classified by the diff
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct Change {
+    #[serde(rename = "put-code")]
+    pub put_code: u64,
+    #[serde(skip)]
+    pub diff: Diff,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub doi: Option<String>,
+    // `last-modified-date` of the stored record (absent for added records)
+    #[serde(
+        rename = "old-last-modified-date",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub old_last_modified: Option<u64>,
+    // `last-modified-date` of the latest summary (absent for deleted records)
+    #[serde(
+        rename = "new-last-modified-date",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub new_last_modified: Option<u64>,
+    // Summary fields that disagreed with the stored record (`--verify`)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub reasons: Vec<&'static str>,
+}
+
+impl Change {
+    // Title and DOI are taken from the latest summary, falling back to the stored record
+    pub(crate) fn new<O, N>(diff: Diff, old: Option<&O>, new: Option<&N>) -> Option<Self>
+    where
+        O: OrcidRecord,
+        N: OrcidRecord,
+    {
+        let put_code = new
+            .map(OrcidRecord::put_code)
+            .or_else(|| old.map(OrcidRecord::put_code))?;
+        let title = new
+            .and_then(OrcidRecord::title)
+            .or_else(|| old.and_then(OrcidRecord::title));
+        let doi = new
+            .and_then(OrcidRecord::doi)
+            .or_else(|| old.and_then(OrcidRecord::doi));
+        Some(Self {
+            put_code,
+            diff,
+            title: title.map(str::to_string),
+            doi: doi.map(str::to_string),
+            old_last_modified: old.map(OrcidRecord::last_modified),
+            new_last_modified: new.map(OrcidRecord::last_modified),
+            reasons: vec![],
+        })
+    }
+}
+
+// Diff of one section of one ORCID iD
+#[derive(Debug, Clone)]
+pub(crate) struct SectionChanges {
+    pub id: OrcidId,
+    pub section: &'static str,
+    // `None` when the summaries were not modified since the last run
+    pub changes: Option<Vec<Change>>,
+    // Put-codes that could not be fetched (`--keep-going` only)
+    pub failed: Vec<u64>,
+}
+
+// Serialized form of `SectionChanges`, grouped by diff category
+#[derive(Serialize)]
+struct SectionEntry<'a> {
+    id: &'a OrcidId,
+    section: &'static str,
+    #[serde(rename = "not-modified")]
+    not_modified: bool,
+    added: Vec<&'a Change>,
+    updated: Vec<&'a Change>,
+    kept: Vec<&'a Change>,
+    deleted: Vec<&'a Change>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    failed: &'a [u64],
+}
+
+impl SectionChanges {
+    fn of(&self, diff: Diff) -> Vec<&Change> {
+        self.changes
+            .iter()
+            .flatten()
+            .filter(|c| c.diff == diff)
+            .collect()
+    }
+
+    fn entry(&self) -> SectionEntry<'_> {
+        SectionEntry {
+            id: &self.id,
+            section: self.section,
+            not_modified: self.changes.is_none(),
+            added: self.of(Diff::Added),
+            updated: self.of(Diff::Updated),
+            kept: self.of(Diff::Kept),
+            deleted: self.of(Diff::Deleted),
+            failed: &self.failed,
+        }
+    }
+}
+
+// Milliseconds since the Unix epoch, like ORCID's own timestamps
+pub(crate) fn epoch_millis(t: SystemTime) -> u64 {
+    t.duration_since(UNIX_EPOCH)
+        .map(|d| u64::try_from(d.as_millis()).unwrap_or(u64::MAX))
+        .unwrap_or_default()
+}
+
+// Document written by `--report`
+#[derive(Serialize)]
+pub(crate) struct RunReport<'a> {
+    pub ids: &'a [OrcidId],
+    #[serde(rename = "started-at")]
+    pub started_at: u64,
+    #[serde(rename = "finished-at")]
+    pub finished_at: u64,
+    // HTTP requests sent, retries included
+    pub requests: u64,
+    #[serde(rename = "dry-run")]
+    pub dry_run: bool,
+    // `complete`, or `partial` when some records could not be fetched
+    pub status: &'static str,
+    #[serde(serialize_with = "serialize_sections")]
+    pub sections: &'a [SectionChanges],
+}
+
+fn serialize_sections<S>(sections: &&[SectionChanges], ser: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    ser.collect_seq(sections.iter().map(SectionChanges::entry))
+}
+
+pub(crate) fn write_report(path: &Path, report: &RunReport) -> Result<()> {
+    write_pretty_json(path, report)
+        .with_context(|| format!("write change report to {}", path.display()))
+}
+
+// Print the planned changes of a dry run to stdout
+pub(crate) fn print_dry_run(plan: &[SectionChanges], format: DryRunFormat) -> Result<()> {
+    let mut stdout = std::io::stdout().lock();
+    match format {
+        DryRunFormat::Json => {
+            let entries: Vec<SectionEntry> = plan.iter().map(SectionChanges::entry).collect();
+            serde_json::to_writer_pretty(&mut stdout, &entries)
+                .context("serialize dry-run plan")?;
+            writeln!(stdout)?;
+        }
+        DryRunFormat::Text => {
+            for (i, s) in plan.iter().enumerate() {
+                if i > 0 {
+                    writeln!(stdout)?;
+                }
+                writeln!(
+                    stdout,
+                    "{} {}: {} added, {} updated, {} kept, {} deleted",
+                    s.id,
+                    s.section,
+                    s.of(Diff::Added).len(),
+                    s.of(Diff::Updated).len(),
+                    s.of(Diff::Kept).len(),
+                    s.of(Diff::Deleted).len(),
+                )?;
+                for c in s.changes.iter().flatten() {
+                    write!(
+                        stdout,
+                        "  {:<8} {:>12}  {}",
+                        c.diff.name(),
+                        c.put_code,
+                        c.title.as_deref().unwrap_or("-")
+                    )?;
+                    if !c.reasons.is_empty() {
+                        write!(stdout, "  (mismatch: {})", c.reasons.join(", "))?;
+                    }
+                    writeln!(stdout)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,238 @@
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::{Serialize, de::DeserializeOwned};
+
+use orcid_works_model::{
+    AffiliationSummary, OrcidFundingDetail, OrcidFundingSummary, OrcidId, OrcidPeerReviewDetail,
+    OrcidPeerReviewSummary, OrcidRecord, OrcidWorkDetail, OrcidWorkSummary,
+};
+
+use crate::api::{
+    ApiClient, Conditional, Validators, fetch_affiliations, fetch_funding_detail, fetch_fundings,
+    fetch_peer_review_detail, fetch_peer_reviews, fetch_works,
+};
+use crate::compare::verify_work_summary;
+use crate::sync::{FetchResult, SyncContext, fetch_each, fetch_work_details};
+
+// Record sections selectable with `--section`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum)]
+pub(crate) enum SectionKind {
+    Works,
+    Employments,
+    Educations,
+    Qualifications,
+    InvitedPositions,
+    Distinctions,
+    Memberships,
+    Services,
+    Fundings,
+    PeerReviews,
+}
+
+impl SectionKind {
+    // Endpoint path segment, also used as the `{section}` placeholder value
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Self::Works => "works",
+            Self::Employments => "employments",
+            Self::Educations => "educations",
+            Self::Qualifications => "qualifications",
+            Self::InvitedPositions => "invited-positions",
+            Self::Distinctions => "distinctions",
+            Self::Memberships => "memberships",
+            Self::Services => "services",
+            Self::Fundings => "fundings",
+            Self::PeerReviews => "peer-reviews",
+        }
+    }
+}
+
+// Summaries listed by a section endpoint
+pub(crate) struct Summaries<T> {
+    pub items: Vec<T>,
+    // Section-level `last-modified-date`
+    pub last_modified_date: Option<u64>,
+}
+
+// A record section synced with the diff-aware logic: list summaries, compare
+// their put-codes and last-modified dates with the stored records, then fetch
+// only the records of added or updated summaries.
+pub(crate) trait Section {
+    type Summary: OrcidRecord;
+    type Record: OrcidRecord + Clone + Serialize + DeserializeOwned;
+
+    fn name(&self) -> &'static str;
+
+    // Fields of a stored record that disagree with its latest summary (`--verify`)
+    fn verify(&self, _summary: &Self::Summary, _record: &Self::Record) -> Vec<&'static str> {
+        vec![]
+    }
+
+    async fn fetch_summaries(
+        &self,
+        client: &ApiClient,
+        id: &OrcidId,
+        validators: Option<&Validators>,
+    ) -> Result<Conditional<Summaries<Self::Summary>>>;
+
+    async fn fetch_records(
+        &self,
+        ctx: &SyncContext,
+        id: &OrcidId,
+        summaries: Vec<Self::Summary>,
+    ) -> Result<FetchResult<Self::Record>>;
+}
+
+// /works + bulk /works/{putcodes}
+pub(crate) struct Works;
+
+impl Section for Works {
+    type Summary = OrcidWorkSummary;
+    type Record = OrcidWorkDetail;
+
+    fn name(&self) -> &'static str {
+        SectionKind::Works.name()
+    }
+
+    fn verify(&self, summary: &Self::Summary, record: &Self::Record) -> Vec<&'static str> {
+        verify_work_summary(summary, &record.summary)
+    }
+
+    async fn fetch_summaries(
+        &self,
+        client: &ApiClient,
+        id: &OrcidId,
+        validators: Option<&Validators>,
+    ) -> Result<Conditional<Summaries<Self::Summary>>> {
+        let works = fetch_works(client, id, validators).await?;
+        Ok(works.map(|works| Summaries {
+            last_modified_date: Some(works.last_modified_date.value),
+            items: works
+                .group
+                .into_iter()
+                .flat_map(|g| g.work_summary)
+                .collect(),
+        }))
+    }
+
+    async fn fetch_records(
+        &self,
+        ctx: &SyncContext,
+        id: &OrcidId,
+        summaries: Vec<Self::Summary>,
+    ) -> Result<FetchResult<Self::Record>> {
+        let putcodes: Vec<u64> = summaries.iter().map(|s| s.put_code).collect();
+        fetch_work_details(ctx, id, &putcodes).await
+    }
+}
+
+// Employments, educations, qualifications, invited positions, distinctions,
+// memberships and services; the summaries already are the full records.
+pub(crate) struct Affiliations(pub SectionKind);
+
+impl Section for Affiliations {
+    type Summary = AffiliationSummary;
+    type Record = AffiliationSummary;
+
+    fn name(&self) -> &'static str {
+        self.0.name()
+    }
+
+    async fn fetch_summaries(
+        &self,
+        client: &ApiClient,
+        id: &OrcidId,
+        validators: Option<&Validators>,
+    ) -> Result<Conditional<Summaries<Self::Summary>>> {
+        let affiliations = fetch_affiliations(client, id, self.name(), validators).await?;
+        Ok(affiliations.map(|affiliations| Summaries {
+            last_modified_date: affiliations.last_modified_date.as_ref().map(|v| v.value),
+            items: affiliations.into_summaries(),
+        }))
+    }
+
+    async fn fetch_records(
+        &self,
+        _ctx: &SyncContext,
+        _id: &OrcidId,
+        summaries: Vec<Self::Summary>,
+    ) -> Result<FetchResult<Self::Record>> {
+        Ok((summaries, vec![]))
+    }
+}
+
+// /fundings + /funding/{putcode}
+pub(crate) struct Fundings;
+
+impl Section for Fundings {
+    type Summary = OrcidFundingSummary;
+    type Record = OrcidFundingDetail;
+
+    fn name(&self) -> &'static str {
+        SectionKind::Fundings.name()
+    }
+
+    async fn fetch_summaries(
+        &self,
+        client: &ApiClient,
+        id: &OrcidId,
+        validators: Option<&Validators>,
+    ) -> Result<Conditional<Summaries<Self::Summary>>> {
+        let fundings = fetch_fundings(client, id, validators).await?;
+        Ok(fundings.map(|fundings| Summaries {
+            last_modified_date: fundings.last_modified_date.as_ref().map(|v| v.value),
+            items: fundings.into_summaries(),
+        }))
+    }
+
+    async fn fetch_records(
+        &self,
+        ctx: &SyncContext,
+        id: &OrcidId,
+        summaries: Vec<Self::Summary>,
+    ) -> Result<FetchResult<Self::Record>> {
+        let putcodes: Vec<u64> = summaries.iter().map(|s| s.put_code).collect();
+        fetch_each(ctx, id, putcodes, |pc| {
+            fetch_funding_detail(&ctx.client, id, pc)
+        })
+        .await
+    }
+}
+
+// /peer-reviews + /peer-review/{putcode}
+pub(crate) struct PeerReviews;
+
+impl Section for PeerReviews {
+    type Summary = OrcidPeerReviewSummary;
+    type Record = OrcidPeerReviewDetail;
+
+    fn name(&self) -> &'static str {
+        SectionKind::PeerReviews.name()
+    }
+
+    async fn fetch_summaries(
+        &self,
+        client: &ApiClient,
+        id: &OrcidId,
+        validators: Option<&Validators>,
+    ) -> Result<Conditional<Summaries<Self::Summary>>> {
+        let reviews = fetch_peer_reviews(client, id, validators).await?;
+        Ok(reviews.map(|reviews| Summaries {
+            last_modified_date: reviews.last_modified_date.as_ref().map(|v| v.value),
+            items: reviews.into_summaries(),
+        }))
+    }
+
+    async fn fetch_records(
+        &self,
+        ctx: &SyncContext,
+        id: &OrcidId,
+        summaries: Vec<Self::Summary>,
+    ) -> Result<FetchResult<Self::Record>> {
+        let putcodes: Vec<u64> = summaries.iter().map(|s| s.put_code).collect();
+        fetch_each(ctx, id, putcodes, |pc| {
+            fetch_peer_review_detail(&ctx.client, id, pc)
+        })
+        .await
+    }
+}
@@ -0,0 +1,524 @@
+use anyhow::{Context, Result};
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use tracing::{Instrument, info, info_span, instrument, warn};
+
+use orcid_works_model::{OrcidBulkItem, OrcidBulkWorks, OrcidId, OrcidRecord, OrcidWorkDetail};
+
+use crate::api::{
+    ApiClient, BULK_MAX, Conditional, Validators, fetch_work_detail, fetch_work_details_bulk,
+};
+use crate::compare::{
+    Diff, added_putcodes, deleted_putcodes, diff_fields, diff_putcodes, kept_putcodes,
+    updated_putcodes,
+};
+use crate::io::JsonStyle;
+use crate::report::Change;
+use crate::section::Section;
+
+// Shared state for syncing one or more ORCID iDs
+pub(crate) struct SyncContext {
+    // Rate-limits every request it sends, retries included
+    pub client: ApiClient,
+    pub concurrency: usize,
+    pub force_fetch: bool,
+    pub keep_going: bool,
+    // Only fetch summaries and diff them; nothing else is fetched or written
+    pub dry_run: bool,
+    // Refetch kept records whose stored content disagrees with the summary
+    pub verify: bool,
+    // Layout of the written records and person JSON
+    pub json: JsonStyle,
+}
+
+// Fetched records plus, in keep-going mode, the put-codes that failed
+pub(crate) type FetchResult<T> = (Vec<T>, Vec<(u64, anyhow::Error)>);
+
+// Result of syncing one section of one ORCID iD
+pub(crate) struct SyncOutcome<T> {
+    // Merged records sorted by put-code; `None` when nothing changed
+    pub records: Option<Vec<T>>,
+    // Put-codes that could not be fetched (`keep_going` only)
+    pub failed: Vec<u64>,
+    // Validators to reuse on the next run; `None` after a partial sync
+    pub cache: Option<SummaryCache>,
+    // Put-codes classified by the diff; `None` when the summaries were not modified
+    pub changes: Option<Vec<Change>>,
+}
+
+// Summary-list validators persisted next to the output of one ORCID iD
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct SummaryCache {
+    #[serde(flatten)]
+    pub validators: Validators,
+    #[serde(rename = "last-modified-date", skip_serializing_if = "Option::is_none")]
+    pub last_modified_date: Option<u64>,
+    // Hash of the records the validators were stored with; the cache is
+    // ignored when the output was edited or replaced in between
+    #[serde(rename = "records-hash")]
+    pub records_hash: String,
+}
+
+// FNV-1a 64 of the bytes written to it
+struct Fnv1a(u64);
+
+impl std::io::Write for Fnv1a {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        for b in buf {
+            self.0 = (self.0 ^ u64::from(*b)).wrapping_mul(0x0000_0100_0000_01b3);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+// FNV-1a 64 over the compact JSON of the records, hashed as it is serialized
+fn records_hash<T: Serialize>(records: &[T]) -> Result<String> {
+    let mut hasher = Fnv1a(0xcbf2_9ce4_8422_2325);
+    serde_json::to_writer(&mut hasher, records).context("hash records")?;
+    Ok(format!("{:016x}", hasher.0))
+}
+
+// Diff-aware sync of one section of one ORCID iD against its existing records
+#[instrument(name = "sync_section", skip_all, fields(id = %id, section = section.name()))]
+pub(crate) async fn sync_section<S: Section>(
+    ctx: &SyncContext,
+    section: &S,
+    id: &OrcidId,
+    existing: Vec<S::Record>,
+    cache: Option<&SummaryCache>,
+) -> Result<SyncOutcome<S::Record>> {
+    // Only trust the validators if the stored records are the ones they were saved with
+    let existing_hash = records_hash(&existing)?;
+    let cache = cache.filter(|c| {
+        !ctx.force_fetch && !ctx.dry_run && !ctx.verify && c.records_hash == existing_hash
+    });
+
+    let mut existing_map: HashMap<u64, S::Record> =
+        existing.into_iter().map(|d| (d.put_code(), d)).collect();
+
+    // Fetch summaries
+    let validators = cache.map(|c| &c.validators).filter(|v| !v.is_empty());
+    info!(conditional = validators.is_some(), "fetching summaries");
+    let fetched_summaries = section
+        .fetch_summaries(&ctx.client, id, validators)
+        .in_current_span()
+        .await
+        .with_context(|| format!("update cache: fetch {} summaries", section.name()))?;
+
+    let (summaries, validators) = match fetched_summaries {
+        Conditional::Modified(summaries, validators) => (summaries, validators),
+        Conditional::NotModified => {
+            info!("summaries not modified - skip fetch & rewrite");
+            return Ok(SyncOutcome {
+                records: None,
+                failed: vec![],
+                cache: cache.cloned(),
+                changes: None,
+            });
+        }
+    };
+
+    // Same section-level last-modified-date as the stored records
+    if let Some(c) = cache
+        && summaries.last_modified_date.is_some()
+        && summaries.last_modified_date == c.last_modified_date
+    {
+        info!("section last-modified-date unchanged - skip fetch & rewrite");
+        return Ok(SyncOutcome {
+            records: None,
+            failed: vec![],
+            cache: Some(SummaryCache {
+                validators,
+                ..c.clone()
+            }),
+            changes: None,
+        });
+    }
+
+    let last_modified_date = summaries.last_modified_date;
+    let summaries: Vec<S::Summary> = summaries.items;
+
+    // Detect changes
+    let mut diff_map = diff_putcodes(&existing_map, &summaries, ctx.force_fetch);
+
+    // Verify kept records against their summaries
+    let mut reasons: HashMap<u64, Vec<&'static str>> = HashMap::new();
+    if ctx.verify {
+        for s in &summaries {
+            let pc = s.put_code();
+            if diff_map.get(&pc) != Some(&Diff::Kept) {
+                continue;
+            }
+            let Some(record) = existing_map.get(&pc) else {
+                continue;
+            };
+            let mismatched = section.verify(s, record);
+            if !mismatched.is_empty() {
+                warn!(%pc, fields = ?mismatched, "stored record disagrees with its summary; refetching");
+                diff_map.insert(pc, Diff::Updated);
+                reasons.insert(pc, mismatched);
+            }
+        }
+        info!(mismatched = reasons.len(), "verified kept records");
+    }
+
+    let added = added_putcodes(&diff_map);
+    let updated = updated_putcodes(&diff_map);
+    let kept = kept_putcodes(&diff_map);
+    let deleted = deleted_putcodes(&diff_map);
+
+    info!(
+        added = added.len(),
+        updated = updated.len(),
+        deleted = deleted.len(),
+        "diff stats"
+    );
+
+    let mut changes: Vec<Change> = summaries
+        .iter()
+        .filter_map(|s| {
+            let diff = *diff_map.get(&s.put_code())?;
+            let mut change = Change::new(diff, existing_map.get(&s.put_code()), Some(s))?;
+            change.reasons = reasons.remove(&s.put_code()).unwrap_or_default();
+            Some(change)
+        })
+        .chain(deleted.iter().filter_map(|pc| {
+            Change::new::<_, S::Summary>(Diff::Deleted, existing_map.get(pc), None)
+        }))
+        .collect();
+    changes.sort_by_key(|c| (c.diff, c.put_code));
+
+    if ctx.dry_run {
+        return Ok(SyncOutcome {
+            records: None,
+            failed: vec![],
+            cache: None,
+            changes: Some(changes),
+        });
+    }
+
+    // Exit if no changes detected
+    let to_fetch: Vec<S::Summary> = summaries
+        .into_iter()
+        .filter(|s| {
+            matches!(
+                diff_map.get(&s.put_code()),
+                Some(Diff::Added | Diff::Updated)
+            )
+        })
+        .collect();
+
+    if to_fetch.len() + deleted.len() == 0 {
+        info!(
+            to_fetch = to_fetch.len(),
+            deleted = deleted.len(),
+            "no changes detected - skip fetch & rewrite"
+        );
+        return Ok(SyncOutcome {
+            records: None,
+            failed: vec![],
+            cache: Some(SummaryCache {
+                validators,
+                last_modified_date,
+                records_hash: existing_hash,
+            }),
+            changes: Some(changes),
+        });
+    }
+
+    let (fetched, failed) = section
+        .fetch_records(ctx, id, to_fetch)
+        .in_current_span()
+        .await?;
+
+    // Failed records keep their previous version (if any) in keep-going mode
+    for (pc, e) in &failed {
+        let kept_previous = existing_map.contains_key(pc);
+        warn!(%pc, kept_previous, err = format!("{e:#}"), "failed to fetch record");
+    }
+
+    // Log which fields of the updated records actually changed
+    for record in &fetched {
+        if let Some(old) = existing_map.get(&record.put_code()) {
+            let fields: Vec<String> = diff_fields(old, record)
+                .into_iter()
+                .map(|c| c.field)
+                .collect();
+            info!(pc = record.put_code(), ?fields, "record updated");
+        }
+    }
+
+    // Merge; kept records are moved out of the map, not copied
+    let mut merged: Vec<S::Record> = kept
+        .into_iter()
+        .chain(failed.iter().map(|(pc, _)| *pc))
+        .filter_map(|pc| existing_map.remove(&pc))
+        .chain(fetched)
+        .collect();
+
+    merged.sort_by_key(|d| d.put_code());
+
+    let cache = if failed.is_empty() {
+        Some(SummaryCache {
+            validators,
+            last_modified_date,
+            records_hash: records_hash(&merged)?,
+        })
+    } else {
+        None
+    };
+
+    Ok(SyncOutcome {
+        records: Some(merged),
+        failed: failed.into_iter().map(|(pc, _)| pc).collect(),
+        cache,
+        changes: Some(changes),
+    })
+}
+
+// Parallel fetch of one record per put-code
+pub(crate) async fn fetch_each<T, F, Fut>(
+    ctx: &SyncContext,
+    id: &OrcidId,
+    putcodes: Vec<u64>,
+    fetch: F,
+) -> Result<FetchResult<T>>
+where
+    F: Fn(u64) -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let batch_span = info_span!("fetch_details_batch", total = putcodes.len());
+    let results: Vec<(u64, Result<T>)> = stream::iter(putcodes)
+        .map(|pc| {
+            let task_span = info_span!("detail_task", %pc);
+            let fut = fetch(pc);
+
+            async move {
+                let res = fut.in_current_span().await;
+                (pc, res)
+            }
+            .instrument(task_span)
+        })
+        .buffer_unordered(ctx.concurrency)
+        .collect::<Vec<_>>()
+        .instrument(batch_span)
+        .await;
+
+    let mut fetched = Vec::with_capacity(results.len());
+    let mut failed = Vec::new();
+    for (pc, res) in results {
+        match res {
+            Ok(detail) => fetched.push(detail),
+            Err(e) if ctx.keep_going => failed.push((pc, e)),
+            Err(e) => return Err(e).with_context(|| format!("batch fetch for ORCID iD {id}")),
+        }
+    }
+
+    Ok((fetched, failed))
+}
+
+// Bulk requests for `to_fetch`: up to BULK_MAX put-codes each, in order
+fn bulk_chunks(to_fetch: &[u64]) -> Vec<Vec<u64>> {
+    to_fetch.chunks(BULK_MAX).map(<[u64]>::to_vec).collect()
+}
+
+// Split the bulk response to `chunk` into the requested works it returned and
+// the put-codes to refetch one by one (item errors and missing works), in
+// request order
+fn split_bulk(chunk: Vec<u64>, res: OrcidBulkWorks) -> (Vec<OrcidWorkDetail>, Vec<u64>) {
+    let mut pending: HashSet<u64> = chunk.iter().copied().collect();
+    let mut works = Vec::with_capacity(chunk.len());
+    for item in res.bulk {
+        match item {
+            OrcidBulkItem::Work(work) => {
+                if pending.remove(&work.summary.put_code) {
+                    works.push(*work);
+                }
+            }
+            OrcidBulkItem::Error(e) => {
+                warn!(
+                    response_code = e.response_code,
+                    developer_message = e.developer_message.as_deref().unwrap_or_default(),
+                    "bulk item error"
+                );
+            }
+        }
+    }
+    let missing = chunk
+        .into_iter()
+        .filter(|pc| pending.contains(pc))
+        .collect();
+    (works, missing)
+}
+
+// Fetch work details in bulk, falling back to single requests for missing items.
+// Returns the fetched details and, in keep-going mode, the put-codes that failed.
+pub(crate) async fn fetch_work_details(
+    ctx: &SyncContext,
+    id: &OrcidId,
+    to_fetch: &[u64],
+) -> Result<FetchResult<OrcidWorkDetail>> {
+    // Bulk fetch work details, BULK_MAX put-codes per request
+    info!("fetching work details");
+    let chunks = bulk_chunks(to_fetch);
+    let bulk_span = info_span!(
+        "fetch_work_details_bulk_batch",
+        total = to_fetch.len(),
+        chunks = chunks.len()
+    );
+    let bulk: Vec<(Vec<u64>, Result<OrcidBulkWorks>)> = stream::iter(chunks)
+        .map(|chunk| {
+            let task_span = info_span!("work_details_bulk_task", len = chunk.len());
+
+            async move {
+                let res = fetch_work_details_bulk(&ctx.client, id, &chunk)
+                    .in_current_span()
+                    .await;
+                (chunk, res)
+            }
+            .instrument(task_span)
+        })
+        .buffer_unordered(ctx.concurrency)
+        .collect::<Vec<_>>()
+        .instrument(bulk_span)
+        .await;
+
+    // Collect bulk works; anything not returned as a work is refetched singly
+    let mut fetched: Vec<OrcidWorkDetail> = Vec::with_capacity(to_fetch.len());
+    let mut fallback: Vec<u64> = Vec::new();
+    for (chunk, res) in bulk {
+        let res = match res {
+            Ok(res) => res,
+            Err(e) if ctx.keep_going => {
+                warn!(err = %e, len = chunk.len(), "bulk request failed; refetching its works one by one");
+                fallback.extend(chunk);
+                continue;
+            }
+            Err(e) => return Err(e).with_context(|| format!("bulk fetch for ORCID iD {id}")),
+        };
+        let (works, missing) = split_bulk(chunk, res);
+        fetched.extend(works);
+        fallback.extend(missing);
+    }
+
+    // Parallel fetch work details that the bulk endpoint did not return
+    let mut failed: Vec<(u64, anyhow::Error)> = Vec::new();
+    if !fallback.is_empty() {
+        info!(
+            fallback = fallback.len(),
+            "fetching remaining work details one by one"
+        );
+        let (singles, single_failed) = fetch_each(ctx, id, fallback, |pc| {
+            fetch_work_detail(&ctx.client, id, pc)
+        })
+        .await?;
+        fetched.extend(singles);
+        failed.extend(single_failed);
+    }
+
+    Ok((fetched, failed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use orcid_works_model::OrcidWorkDetailFile;
+
+    const FIXTURE: &str = include_str!("../tests/fixtures/works.json");
+
+    fn work(put_code: u64) -> serde_json::Value {
+        let file: serde_json::Value = serde_json::from_str(FIXTURE).expect("fixture parses");
+        let mut work = file["records"][0].clone();
+        work["put-code"] = put_code.into();
+        work
+    }
+
+    fn bulk(items: Vec<serde_json::Value>) -> OrcidBulkWorks {
+        serde_json::from_value(serde_json::json!({ "bulk": items })).expect("bulk parses")
+    }
+
+    fn error(code: u16) -> serde_json::Value {
+        serde_json::json!({ "error": { "response-code": code, "developer-message": "boom" } })
+    }
+
+    #[test]
+    fn records_hash_is_fnv1a_of_the_compact_json() {
+        assert_eq!(records_hash::<u64>(&[]).unwrap(), "09612b07b5ecb5a5");
+
+        // Same value as hashing the serialized bytes, so stored caches stay valid
+        let file: OrcidWorkDetailFile = serde_json::from_str(FIXTURE).unwrap();
+        let bytes = serde_json::to_vec(&file.records).unwrap();
+        let mut buffered = Fnv1a(0xcbf2_9ce4_8422_2325);
+        std::io::Write::write_all(&mut buffered, &bytes).unwrap();
+        assert_eq!(
+            records_hash(&file.records).unwrap(),
+            format!("{:016x}", buffered.0)
+        );
+        assert_ne!(
+            records_hash(&file.records).unwrap(),
+            records_hash(&file.records[1..]).unwrap()
+        );
+    }
+
+    #[test]
+    fn chunks_hold_at_most_bulk_max_put_codes() {
+        assert!(bulk_chunks(&[]).is_empty());
+
+        let one: Vec<u64> = (1..=BULK_MAX as u64).collect();
+        assert_eq!(bulk_chunks(&one), vec![one.clone()]);
+
+        let put_codes: Vec<u64> = (1..=2 * BULK_MAX as u64 + 1).collect();
+        let chunks = bulk_chunks(&put_codes);
+        let lens: Vec<usize> = chunks.iter().map(Vec::len).collect();
+        assert_eq!(lens, [BULK_MAX, BULK_MAX, 1]);
+        assert_eq!(chunks.concat(), put_codes);
+    }
+
+    #[test]
+    fn bulk_item_errors_fall_back_to_single_fetches() {
+        let res = bulk(vec![
+            serde_json::json!({ "work": work(1) }),
+            error(500),
+            serde_json::json!({ "work": work(3) }),
+        ]);
+        let (works, missing) = split_bulk(vec![1, 2, 3], res);
+        let put_codes: Vec<u64> = works.iter().map(|w| w.put_code()).collect();
+        assert_eq!(put_codes, [1, 3]);
+        assert_eq!(missing, [2]);
+    }
+
+    #[test]
+    fn missing_and_unrequested_bulk_works_are_handled() {
+        // Work 2 is left out entirely; work 9 was never asked for; 1 twice
+        let res = bulk(vec![
+            serde_json::json!({ "work": work(9) }),
+            serde_json::json!({ "work": work(1) }),
+            serde_json::json!({ "work": work(1) }),
+            error(404),
+        ]);
+        let (works, missing) = split_bulk(vec![3, 2, 1], res);
+        let put_codes: Vec<u64> = works.iter().map(|w| w.put_code()).collect();
+        assert_eq!(put_codes, [1]);
+        assert_eq!(missing, [3, 2]);
+    }
+
+    #[test]
+    fn fixture_works_parse_as_bulk_items() {
+        let file: OrcidWorkDetailFile = serde_json::from_str(FIXTURE).expect("fixture parses");
+        let items = file
+            .records
+            .iter()
+            .map(|w| serde_json::json!({ "work": w }))
+            .collect();
+        let put_codes: Vec<u64> = file.records.iter().map(|w| w.put_code()).collect();
+        let (works, missing) = split_bulk(put_codes, bulk(items));
+        assert_eq!(works, file.records);
+        assert!(missing.is_empty());
+    }
+}
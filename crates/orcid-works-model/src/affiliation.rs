@@ -0,0 +1,223 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{ExternalIds, OrcidRecord, Source, Value};
+
+// Date with optional month/day, as used by affiliations and fundings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FuzzyDate {
+    pub year: Value<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub month: Option<Value<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub day: Option<Value<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrganizationAddress {
+    pub city: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    pub country: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DisambiguatedOrganization {
+    #[serde(rename = "disambiguated-organization-identifier")]
+    pub disambiguated_organization_identifier: String,
+    #[serde(rename = "disambiguation-source")]
+    pub disambiguation_source: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Organization {
+    pub name: String,
+    pub address: OrganizationAddress,
+    #[serde(
+        rename = "disambiguated-organization",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub disambiguated_organization: Option<DisambiguatedOrganization>,
+}
+
+// Employment, education, qualification, invited position, distinction, membership
+// or service. The v3.0 detail endpoints return the same fields as the summary, so
+// the summary is stored as the record itself.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AffiliationSummary {
+    #[serde(rename = "put-code")]
+    pub put_code: u64,
+    #[serde(rename = "created-date")]
+    pub created_date: Value<u64>,
+    #[serde(rename = "last-modified-date")]
+    pub last_modified_date: Value<u64>,
+    pub source: Source,
+    #[serde(rename = "department-name", skip_serializing_if = "Option::is_none")]
+    pub department_name: Option<String>,
+    #[serde(rename = "role-title", skip_serializing_if = "Option::is_none")]
+    pub role_title: Option<String>,
+    #[serde(rename = "start-date", skip_serializing_if = "Option::is_none")]
+    pub start_date: Option<FuzzyDate>,
+    #[serde(rename = "end-date", skip_serializing_if = "Option::is_none")]
+    pub end_date: Option<FuzzyDate>,
+    pub organization: Organization,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<Value<String>>,
+    #[serde(rename = "external-ids", skip_serializing_if = "Option::is_none")]
+    pub external_ids: Option<ExternalIds>,
+    #[serde(rename = "display-index", skip_serializing_if = "Option::is_none")]
+    pub display_index: Option<String>,
+    pub visibility: String,
+    pub path: String,
+}
+
+impl OrcidRecord for AffiliationSummary {
+    fn put_code(&self) -> u64 {
+        self.put_code
+    }
+
+    fn last_modified(&self) -> u64 {
+        self.last_modified_date.value
+    }
+
+    fn title(&self) -> Option<&str> {
+        Some(
+            self.role_title
+                .as_deref()
+                .unwrap_or(&self.organization.name),
+        )
+    }
+}
+
+// One element of `summaries`, tagged by affiliation type
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AffiliationSummaryItem {
+    #[serde(rename = "employment-summary")]
+    Employment(AffiliationSummary),
+    #[serde(rename = "education-summary")]
+    Education(AffiliationSummary),
+    #[serde(rename = "qualification-summary")]
+    Qualification(AffiliationSummary),
+    #[serde(rename = "invited-position-summary")]
+    InvitedPosition(AffiliationSummary),
+    #[serde(rename = "distinction-summary")]
+    Distinction(AffiliationSummary),
+    #[serde(rename = "membership-summary")]
+    Membership(AffiliationSummary),
+    #[serde(rename = "service-summary")]
+    Service(AffiliationSummary),
+}
+
+impl AffiliationSummaryItem {
+    pub fn summary(&self) -> &AffiliationSummary {
+        match self {
+            Self::Employment(s)
+            | Self::Education(s)
+            | Self::Qualification(s)
+            | Self::InvitedPosition(s)
+            | Self::Distinction(s)
+            | Self::Membership(s)
+            | Self::Service(s) => s,
+        }
+    }
+
+    pub fn into_summary(self) -> AffiliationSummary {
+        match self {
+            Self::Employment(s)
+            | Self::Education(s)
+            | Self::Qualification(s)
+            | Self::InvitedPosition(s)
+            | Self::Distinction(s)
+            | Self::Membership(s)
+            | Self::Service(s) => s,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AffiliationGroup {
+    #[serde(rename = "last-modified-date", skip_serializing_if = "Option::is_none")]
+    pub last_modified_date: Option<Value<u64>>,
+    #[serde(rename = "external-ids", skip_serializing_if = "Option::is_none")]
+    pub external_ids: Option<ExternalIds>,
+    pub summaries: Vec<AffiliationSummaryItem>,
+}
+
+// Response JSON from {BASE}/{id}/employments, /educations, /qualifications,
+// /invited-positions, /distinctions, /memberships and /services
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrcidAffiliations {
+    #[serde(rename = "last-modified-date", skip_serializing_if = "Option::is_none")]
+    pub last_modified_date: Option<Value<u64>>,
+    #[serde(rename = "affiliation-group")]
+    pub affiliation_group: Vec<AffiliationGroup>,
+    pub path: String,
+}
+
+impl OrcidAffiliations {
+    pub fn from_reader<R: std::io::Read>(reader: R) -> Result<Self, serde_json::Error> {
+        serde_json::from_reader(reader)
+    }
+
+    // Summaries of all groups, in response order
+    pub fn into_summaries(self) -> Vec<AffiliationSummary> {
+        self.affiliation_group
+            .into_iter()
+            .flat_map(|g| g.summaries)
+            .map(AffiliationSummaryItem::into_summary)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EMPLOYMENTS: &str = include_str!("../tests/fixtures/employments.json");
+
+    #[test]
+    fn parses_an_affiliations_response() {
+        let response = OrcidAffiliations::from_reader(EMPLOYMENTS.as_bytes()).unwrap();
+        assert_eq!(response.path, "/0000-0002-1825-0097/employments");
+        assert_eq!(
+            response.last_modified_date.as_ref().unwrap().value,
+            1700000300000
+        );
+        assert!(matches!(
+            response.affiliation_group[0].summaries[0],
+            AffiliationSummaryItem::Employment(_)
+        ));
+
+        let summaries = response.into_summaries();
+        let put_codes: Vec<u64> = summaries.iter().map(OrcidRecord::put_code).collect();
+        assert_eq!(put_codes, [2001, 2002]);
+
+        let first = &summaries[0];
+        assert_eq!(first.last_modified(), 1700000300000);
+        assert_eq!(first.title(), Some("Professor"));
+        assert_eq!(first.department_name.as_deref(), Some("Psychoceramics"));
+        assert_eq!(first.end_date, None);
+        let org = first
+            .organization
+            .disambiguated_organization
+            .as_ref()
+            .unwrap();
+        assert_eq!(org.disambiguation_source, "ROR");
+
+        // Without a role title the organization names the record
+        assert_eq!(summaries[1].title(), Some("Wesleyan University"));
+        assert_eq!(summaries[1].organization.address.region, None);
+    }
+
+    #[test]
+    fn affiliation_records_round_trip() {
+        let summaries = OrcidAffiliations::from_reader(EMPLOYMENTS.as_bytes())
+            .unwrap()
+            .into_summaries();
+        let json = serde_json::to_string_pretty(&summaries).unwrap();
+        let back: Vec<AffiliationSummary> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, summaries);
+        assert_eq!(serde_json::to_string_pretty(&back).unwrap(), json);
+        // Nulls of the API response are not written back
+        assert!(!json.contains("null"));
+    }
+}
@@ -0,0 +1,201 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Contributors, ExternalIds, FuzzyDate, OrcidRecord, Organization, Source, Title, Value,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Amount {
+    pub value: String,
+    #[serde(rename = "currency-code")]
+    pub currency_code: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrcidFundingSummary {
+    #[serde(rename = "put-code")]
+    pub put_code: u64,
+    #[serde(rename = "created-date")]
+    pub created_date: Value<u64>,
+    #[serde(rename = "last-modified-date")]
+    pub last_modified_date: Value<u64>,
+    pub source: Source,
+    pub title: Title,
+    #[serde(rename = "external-ids", skip_serializing_if = "Option::is_none")]
+    pub external_ids: Option<ExternalIds>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<Value<String>>,
+    pub r#type: String,
+    #[serde(rename = "start-date", skip_serializing_if = "Option::is_none")]
+    pub start_date: Option<FuzzyDate>,
+    #[serde(rename = "end-date", skip_serializing_if = "Option::is_none")]
+    pub end_date: Option<FuzzyDate>,
+    pub organization: Organization,
+    pub visibility: String,
+    pub path: String,
+    #[serde(rename = "display-index", skip_serializing_if = "Option::is_none")]
+    pub display_index: Option<String>,
+}
+
+impl OrcidRecord for OrcidFundingSummary {
+    fn put_code(&self) -> u64 {
+        self.put_code
+    }
+
+    fn last_modified(&self) -> u64 {
+        self.last_modified_date.value
+    }
+
+    fn title(&self) -> Option<&str> {
+        Some(&self.title.title.value)
+    }
+}
+
+// Response from /{id}/funding/{putcode}
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrcidFundingDetail {
+    #[serde(rename = "put-code")]
+    pub put_code: u64,
+    #[serde(rename = "created-date")]
+    pub created_date: Value<u64>,
+    #[serde(rename = "last-modified-date")]
+    pub last_modified_date: Value<u64>,
+    pub source: Source,
+    pub r#type: String,
+    #[serde(
+        rename = "organization-defined-type",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub organization_defined_type: Option<Value<String>>,
+    pub title: Title,
+    #[serde(rename = "short-description", skip_serializing_if = "Option::is_none")]
+    pub short_description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount: Option<Amount>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<Value<String>>,
+    #[serde(rename = "start-date", skip_serializing_if = "Option::is_none")]
+    pub start_date: Option<FuzzyDate>,
+    #[serde(rename = "end-date", skip_serializing_if = "Option::is_none")]
+    pub end_date: Option<FuzzyDate>,
+    #[serde(rename = "external-ids", skip_serializing_if = "Option::is_none")]
+    pub external_ids: Option<ExternalIds>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contributors: Option<Contributors>,
+    pub organization: Organization,
+    pub visibility: String,
+    pub path: String,
+}
+
+impl OrcidRecord for OrcidFundingDetail {
+    fn put_code(&self) -> u64 {
+        self.put_code
+    }
+
+    fn last_modified(&self) -> u64 {
+        self.last_modified_date.value
+    }
+
+    fn title(&self) -> Option<&str> {
+        Some(&self.title.title.value)
+    }
+}
+
+impl OrcidFundingDetail {
+    pub fn from_reader<R: std::io::Read>(reader: R) -> Result<Self, serde_json::Error> {
+        serde_json::from_reader(reader)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FundingGroup {
+    #[serde(rename = "last-modified-date", skip_serializing_if = "Option::is_none")]
+    pub last_modified_date: Option<Value<u64>>,
+    #[serde(rename = "external-ids", skip_serializing_if = "Option::is_none")]
+    pub external_ids: Option<ExternalIds>,
+    #[serde(rename = "funding-summary")]
+    pub funding_summary: Vec<OrcidFundingSummary>,
+}
+
+// Response JSON from {BASE}/{id}/fundings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrcidFundings {
+    #[serde(rename = "last-modified-date", skip_serializing_if = "Option::is_none")]
+    pub last_modified_date: Option<Value<u64>>,
+    pub group: Vec<FundingGroup>,
+    pub path: String,
+}
+
+impl OrcidFundings {
+    pub fn from_reader<R: std::io::Read>(reader: R) -> Result<Self, serde_json::Error> {
+        serde_json::from_reader(reader)
+    }
+
+    // Summaries of all groups, in response order
+    pub fn into_summaries(self) -> Vec<OrcidFundingSummary> {
+        self.group
+            .into_iter()
+            .flat_map(|g| g.funding_summary)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FUNDINGS: &str = include_str!("../tests/fixtures/fundings.json");
+    const FUNDING: &str = include_str!("../tests/fixtures/funding.json");
+
+    #[test]
+    fn parses_a_fundings_response() {
+        let response = OrcidFundings::from_reader(FUNDINGS.as_bytes()).unwrap();
+        assert_eq!(response.path, "/0000-0002-1825-0097/fundings");
+        assert_eq!(
+            response.last_modified_date.as_ref().unwrap().value,
+            1700000400000
+        );
+
+        let summaries = response.into_summaries();
+        let put_codes: Vec<u64> = summaries.iter().map(OrcidRecord::put_code).collect();
+        assert_eq!(put_codes, [3001, 3002]);
+        assert_eq!(summaries[0].title(), Some("Cracks in pots"));
+        assert_eq!(summaries[0].r#type, "grant");
+        assert_eq!(summaries[1].last_modified(), 1610000000000);
+    }
+
+    #[test]
+    fn parses_a_funding_detail() {
+        let detail = OrcidFundingDetail::from_reader(FUNDING.as_bytes()).unwrap();
+        assert_eq!(detail.put_code(), 3001);
+        assert_eq!(detail.last_modified(), 1700000400000);
+        assert_eq!(detail.title(), Some("Cracks in pots"));
+        let amount = detail.amount.as_ref().unwrap();
+        assert_eq!(
+            (amount.value.as_str(), amount.currency_code.as_str()),
+            ("250000", "USD")
+        );
+        assert_eq!(detail.organization_defined_type, None);
+        let contributors = detail
+            .contributors
+            .as_ref()
+            .unwrap()
+            .contributor
+            .as_ref()
+            .unwrap();
+        assert_eq!(
+            contributors[0].credit_name.as_ref().unwrap().value,
+            "Josiah Carberry"
+        );
+    }
+
+    #[test]
+    fn funding_records_round_trip() {
+        let detail = OrcidFundingDetail::from_reader(FUNDING.as_bytes()).unwrap();
+        let json = serde_json::to_string_pretty(&[&detail]).unwrap();
+        let back: Vec<OrcidFundingDetail> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, [detail]);
+        assert_eq!(serde_json::to_string_pretty(&back).unwrap(), json);
+        assert!(!json.contains("null"));
+    }
+}
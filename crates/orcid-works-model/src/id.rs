@@ -0,0 +1,260 @@
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+// Hosts accepted in `https://<host>/<id>` URIs
+const URI_HOSTS: [&str; 3] = ["orcid.org", "www.orcid.org", "sandbox.orcid.org"];
+
+// ORCID iD `xxxx-xxxx-xxxx-xxxX` with a verified ISO 7064 MOD 11-2 check digit.
+// Parses bare iDs and `https://orcid.org/...` URIs; always stored in bare form.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct OrcidId(String);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OrcidIdError {
+    // Not `xxxx-xxxx-xxxx-xxxX` (optionally behind an orcid.org URI)
+    Syntax(String),
+    // Well-formed, but the last character is not the MOD 11-2 check digit
+    Checksum { id: String, expected: char },
+}
+
+impl fmt::Display for OrcidIdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax(s) => write!(
+                f,
+                "invalid ORCID iD {s:?}: expected xxxx-xxxx-xxxx-xxxX or https://orcid.org/xxxx-xxxx-xxxx-xxxX"
+            ),
+            Self::Checksum { id, expected } => write!(
+                f,
+                "invalid ORCID iD {id:?}: check digit should be {expected:?}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for OrcidIdError {}
+
+impl OrcidId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    // Canonical URI form, e.g. `https://orcid.org/0000-0002-1825-0097`
+    pub fn uri(&self) -> String {
+        self.uri_on("orcid.org")
+    }
+
+    // URI on the given registry host, e.g. `sandbox.orcid.org`
+    pub fn uri_on(&self, host: &str) -> String {
+        format!("https://{host}/{}", self.0)
+    }
+}
+
+// ISO 7064 MOD 11-2 check digit over the first 15 digits
+fn check_digit(digits: &[u32]) -> char {
+    let total = digits.iter().fold(0, |acc, d| (acc + d) * 2);
+    match (12 - total % 11) % 11 {
+        10 => 'X',
+        n => char::from_digit(n, 10).unwrap_or('?'),
+    }
+}
+
+// Strip an optional `http(s)://<orcid host>/` prefix and trailing slash
+fn strip_uri(s: &str) -> &str {
+    let rest = s
+        .strip_prefix("https://")
+        .or_else(|| s.strip_prefix("http://"))
+        .unwrap_or(s);
+    URI_HOSTS
+        .iter()
+        .find_map(|host| rest.strip_prefix(host)?.strip_prefix('/'))
+        .map(|id| id.trim_end_matches('/'))
+        .unwrap_or(s)
+}
+
+impl FromStr for OrcidId {
+    type Err = OrcidIdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let raw = strip_uri(s.trim());
+        let syntax = || OrcidIdError::Syntax(s.to_string());
+
+        let blocks: Vec<&str> = raw.split('-').collect();
+        if blocks.len() != 4 || blocks.iter().any(|b| b.len() != 4) {
+            return Err(syntax());
+        }
+
+        let chars: Vec<char> = blocks.concat().chars().collect();
+        let (last, body) = chars.split_last().ok_or_else(syntax)?;
+        let digits: Vec<u32> = body
+            .iter()
+            .map(|c| c.to_digit(10))
+            .collect::<Option<_>>()
+            .ok_or_else(syntax)?;
+        let last = last.to_ascii_uppercase();
+        if !(last.is_ascii_digit() || last == 'X') {
+            return Err(syntax());
+        }
+
+        let id =
+            format!("{}-{}-{}-{}", blocks[0], blocks[1], blocks[2], blocks[3]).to_ascii_uppercase();
+        let expected = check_digit(&digits);
+        if last != expected {
+            return Err(OrcidIdError::Checksum { id, expected });
+        }
+
+        Ok(Self(id))
+    }
+}
+
+impl TryFrom<String> for OrcidId {
+    type Error = OrcidIdError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<OrcidId> for String {
+    fn from(id: OrcidId) -> Self {
+        id.0
+    }
+}
+
+impl AsRef<str> for OrcidId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for OrcidId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Result<OrcidId, OrcidIdError> {
+        s.parse()
+    }
+
+    #[test]
+    fn accepts_known_valid_ids() {
+        for id in ["0000-0002-1825-0097", "0000-0002-9079-593X"] {
+            assert_eq!(parse(id).unwrap().as_str(), id);
+        }
+    }
+
+    #[test]
+    fn normalizes_lowercase_check_digit() {
+        assert_eq!(
+            parse("0000-0002-9079-593x").unwrap().as_str(),
+            "0000-0002-9079-593X"
+        );
+    }
+
+    #[test]
+    fn check_digit_matches_iso_7064() {
+        let digits =
+            |s: &str| -> Vec<u32> { s.chars().filter_map(|c| c.to_digit(10)).take(15).collect() };
+        assert_eq!(check_digit(&digits("0000-0002-1825-009")), '7');
+        assert_eq!(check_digit(&digits("0000-0002-9079-593")), 'X');
+        assert_eq!(check_digit(&digits("0000-0001-5109-370")), '0');
+    }
+
+    #[test]
+    fn rejects_bad_check_digit() {
+        assert_eq!(
+            parse("0000-0002-1825-0098"),
+            Err(OrcidIdError::Checksum {
+                id: "0000-0002-1825-0098".to_string(),
+                expected: '7',
+            })
+        );
+        assert_eq!(
+            parse("0000-0002-9079-5930"),
+            Err(OrcidIdError::Checksum {
+                id: "0000-0002-9079-5930".to_string(),
+                expected: 'X',
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_bad_block_layout() {
+        for bad in [
+            "",
+            "0000-0002-1825",
+            "0000-0002-1825-009",
+            "0000-0002-1825-00977",
+            "000-00002-1825-0097",
+            "0000000218250097",
+            "0000_0002_1825_0097",
+            "0000-0002-1825-0097-",
+            "000A-0002-1825-0097",
+            "0000-0002-1825-009Y",
+            "0000-0002-1825-X097",
+        ] {
+            assert_eq!(
+                parse(bad),
+                Err(OrcidIdError::Syntax(bad.to_string())),
+                "{bad:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn strips_orcid_uris() {
+        for uri in [
+            "https://orcid.org/0000-0002-1825-0097",
+            "http://orcid.org/0000-0002-1825-0097",
+            "https://www.orcid.org/0000-0002-1825-0097",
+            "http://sandbox.orcid.org/0000-0002-1825-0097",
+            "https://sandbox.orcid.org/0000-0002-1825-0097/",
+            "  https://orcid.org/0000-0002-1825-0097  ",
+        ] {
+            assert_eq!(
+                parse(uri).unwrap().as_str(),
+                "0000-0002-1825-0097",
+                "{uri:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_other_hosts() {
+        let uri = "https://example.org/0000-0002-1825-0097";
+        assert_eq!(parse(uri), Err(OrcidIdError::Syntax(uri.to_string())));
+    }
+
+    #[test]
+    fn error_messages_name_the_input() {
+        let syntax = parse("nope").unwrap_err().to_string();
+        assert!(syntax.contains("\"nope\""), "{syntax}");
+        let checksum = parse("0000-0002-1825-0098").unwrap_err().to_string();
+        assert!(checksum.contains("check digit should be '7'"), "{checksum}");
+    }
+
+    #[test]
+    fn uris_and_serde() {
+        let id = parse("0000-0002-1825-0097").unwrap();
+        assert_eq!(id.uri(), "https://orcid.org/0000-0002-1825-0097");
+        assert_eq!(
+            id.uri_on("sandbox.orcid.org"),
+            "https://sandbox.orcid.org/0000-0002-1825-0097"
+        );
+
+        assert_eq!(
+            serde_json::to_string(&id).unwrap(),
+            "\"0000-0002-1825-0097\""
+        );
+        let back: OrcidId =
+            serde_json::from_str("\"https://orcid.org/0000-0002-1825-0097\"").unwrap();
+        assert_eq!(back, id);
+        assert!(serde_json::from_str::<OrcidId>("\"0000-0002-1825-0098\"").is_err());
+    }
+}
@@ -1,4 +1,37 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+mod affiliation;
+mod funding;
+mod id;
+mod peer_review;
+mod person;
+pub use affiliation::{
+    AffiliationGroup, AffiliationSummary, AffiliationSummaryItem, DisambiguatedOrganization,
+    FuzzyDate, OrcidAffiliations, Organization, OrganizationAddress,
+};
+pub use funding::{Amount, FundingGroup, OrcidFundingDetail, OrcidFundingSummary, OrcidFundings};
+pub use id::{OrcidId, OrcidIdError};
+pub use peer_review::{
+    OrcidPeerReviewDetail, OrcidPeerReviewSummary, OrcidPeerReviews, PeerReviewDuplicates,
+    PeerReviewGroup,
+};
+pub use person::{
+    Address, Addresses, Biography, Email, Emails, Keywords, OrcidPerson, OtherNames, PersonContent,
+    PersonExternalIdentifier, PersonExternalIdentifiers, PersonName, ResearcherUrl, ResearcherUrls,
+};
+
+// Put-code and last-modified timestamp shared by every record section
+pub trait OrcidRecord {
+    fn put_code(&self) -> u64;
+    fn last_modified(&self) -> u64;
+    // Human-readable label for listings and reports
+    fn title(&self) -> Option<&str>;
+    // DOI of the record itself, if any
+    fn doi(&self) -> Option<&str> {
+        None
+    }
+}
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Value<T> {
@@ -98,6 +131,22 @@ pub struct ExternalIds {
     pub external_id: Option<Vec<ExternalId>>,
 }
 
+impl ExternalIds {
+    // Value of the first identifier of `id_type` (e.g. "doi"), preferring
+    // `self` relationships over `part-of`, `version-of`, ...
+    pub fn find(&self, id_type: &str) -> Option<&str> {
+        let ids = self.external_id.as_deref()?;
+        let mut of_type = ids
+            .iter()
+            .filter(|e| e.external_id_type.eq_ignore_ascii_case(id_type));
+        of_type
+            .clone()
+            .find(|e| e.external_id_relationship == "self")
+            .or_else(|| of_type.next())
+            .map(|e| e.external_id_value.as_str())
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct OrcidWorkSummary {
     #[serde(rename = "put-code")]
@@ -155,6 +204,42 @@ pub struct OrcidWorkDetail {
     pub country: Option<Value<String>>,
 }
 
+impl OrcidRecord for OrcidWorkSummary {
+    fn put_code(&self) -> u64 {
+        self.put_code
+    }
+
+    fn last_modified(&self) -> u64 {
+        self.last_modified_date.value
+    }
+
+    fn title(&self) -> Option<&str> {
+        Some(&self.title.title.value)
+    }
+
+    fn doi(&self) -> Option<&str> {
+        self.external_ids.find("doi")
+    }
+}
+
+impl OrcidRecord for OrcidWorkDetail {
+    fn put_code(&self) -> u64 {
+        self.summary.put_code
+    }
+
+    fn last_modified(&self) -> u64 {
+        self.summary.last_modified_date.value
+    }
+
+    fn title(&self) -> Option<&str> {
+        self.summary.title()
+    }
+
+    fn doi(&self) -> Option<&str> {
+        self.summary.doi()
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct WorkGroup {
     #[serde(rename = "last-modified-date")]
@@ -180,19 +265,85 @@ impl OrcidWorks {
     }
 }
 
+// Error body returned by the ORCID API, e.g. for a failed item of a bulk request
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrcidError {
+    #[serde(rename = "response-code")]
+    pub response_code: u16,
+    #[serde(rename = "developer-message", skip_serializing_if = "Option::is_none")]
+    pub developer_message: Option<String>,
+    #[serde(rename = "user-message", skip_serializing_if = "Option::is_none")]
+    pub user_message: Option<String>,
+    #[serde(rename = "error-code", skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<u32>,
+    #[serde(rename = "more-info", skip_serializing_if = "Option::is_none")]
+    pub more_info: Option<String>,
+}
+
+// One element of a bulk response: either `{ "work": {...} }` or `{ "error": {...} }`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrcidBulkItem {
+    #[serde(rename = "work")]
+    Work(Box<OrcidWorkDetail>),
+    #[serde(rename = "error")]
+    Error(OrcidError),
+}
+
+// Response JSON from {BASE}/{id}/works/{putcode,putcode,...}
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrcidBulkWorks {
+    pub bulk: Vec<OrcidBulkItem>,
+}
+
+impl OrcidBulkWorks {
+    pub fn from_reader<R: std::io::Read>(reader: R) -> Result<Self, serde_json::Error> {
+        serde_json::from_reader(reader)
+    }
+}
+
 // On‑disk JSON wrapper: `{ "records": [ ... ] }`.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
-pub struct OrcidWorkDetailFile {
-    pub records: Vec<OrcidWorkDetail>,
+pub struct OrcidRecordFile<T> {
+    pub records: Vec<T>,
 }
 
+impl<T> Default for OrcidRecordFile<T> {
+    fn default() -> Self {
+        Self { records: vec![] }
+    }
+}
+
+// On‑disk JSON for several iDs, keyed by iD: `{ "<id>": { "records": [ ... ] }, ... }`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct OrcidCombinedFile<T> {
+    pub ids: BTreeMap<OrcidId, OrcidRecordFile<T>>,
+}
+
+impl<T> Default for OrcidCombinedFile<T> {
+    fn default() -> Self {
+        Self {
+            ids: BTreeMap::new(),
+        }
+    }
+}
+
+pub type OrcidWorkDetailFile = OrcidRecordFile<OrcidWorkDetail>;
+pub type OrcidWorkDetailCombinedFile = OrcidCombinedFile<OrcidWorkDetail>;
+
 impl OrcidWorkDetail {
     pub fn from_reader<R: std::io::Read>(reader: R) -> Result<Self, serde_json::Error> {
         serde_json::from_reader(reader)
     }
 }
 
-impl OrcidWorkDetailFile {
+impl<T: serde::de::DeserializeOwned> OrcidRecordFile<T> {
+    pub fn from_reader<R: std::io::Read>(reader: R) -> Result<Self, serde_json::Error> {
+        serde_json::from_reader(reader)
+    }
+}
+
+impl<T: serde::de::DeserializeOwned> OrcidCombinedFile<T> {
     pub fn from_reader<R: std::io::Read>(reader: R) -> Result<Self, serde_json::Error> {
         serde_json::from_reader(reader)
     }
@@ -0,0 +1,225 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{ExternalId, ExternalIds, FuzzyDate, OrcidRecord, Organization, Source, Title, Value};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrcidPeerReviewSummary {
+    #[serde(rename = "put-code")]
+    pub put_code: u64,
+    #[serde(rename = "created-date")]
+    pub created_date: Value<u64>,
+    #[serde(rename = "last-modified-date")]
+    pub last_modified_date: Value<u64>,
+    pub source: Source,
+    #[serde(rename = "reviewer-role", skip_serializing_if = "Option::is_none")]
+    pub reviewer_role: Option<String>,
+    #[serde(rename = "external-ids", skip_serializing_if = "Option::is_none")]
+    pub external_ids: Option<ExternalIds>,
+    #[serde(rename = "review-url", skip_serializing_if = "Option::is_none")]
+    pub review_url: Option<Value<String>>,
+    #[serde(rename = "review-type", skip_serializing_if = "Option::is_none")]
+    pub review_type: Option<String>,
+    #[serde(rename = "completion-date", skip_serializing_if = "Option::is_none")]
+    pub completion_date: Option<FuzzyDate>,
+    #[serde(rename = "review-group-id")]
+    pub review_group_id: String,
+    #[serde(
+        rename = "convening-organization",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub convening_organization: Option<Organization>,
+    pub visibility: String,
+    pub path: String,
+    #[serde(rename = "display-index", skip_serializing_if = "Option::is_none")]
+    pub display_index: Option<String>,
+}
+
+impl OrcidRecord for OrcidPeerReviewSummary {
+    fn put_code(&self) -> u64 {
+        self.put_code
+    }
+
+    fn last_modified(&self) -> u64 {
+        self.last_modified_date.value
+    }
+
+    fn title(&self) -> Option<&str> {
+        match &self.convening_organization {
+            Some(org) => Some(&org.name),
+            None => Some(&self.review_group_id),
+        }
+    }
+}
+
+// Response from /{id}/peer-review/{putcode}
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrcidPeerReviewDetail {
+    #[serde(rename = "put-code")]
+    pub put_code: u64,
+    #[serde(rename = "created-date")]
+    pub created_date: Value<u64>,
+    #[serde(rename = "last-modified-date")]
+    pub last_modified_date: Value<u64>,
+    pub source: Source,
+    #[serde(rename = "reviewer-role", skip_serializing_if = "Option::is_none")]
+    pub reviewer_role: Option<String>,
+    #[serde(rename = "review-identifiers", skip_serializing_if = "Option::is_none")]
+    pub review_identifiers: Option<ExternalIds>,
+    #[serde(rename = "review-url", skip_serializing_if = "Option::is_none")]
+    pub review_url: Option<Value<String>>,
+    #[serde(rename = "review-type", skip_serializing_if = "Option::is_none")]
+    pub review_type: Option<String>,
+    #[serde(
+        rename = "review-completion-date",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub review_completion_date: Option<FuzzyDate>,
+    #[serde(rename = "review-group-id")]
+    pub review_group_id: String,
+    #[serde(
+        rename = "subject-external-identifier",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub subject_external_identifier: Option<ExternalId>,
+    #[serde(
+        rename = "subject-container-name",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub subject_container_name: Option<Value<String>>,
+    #[serde(rename = "subject-type", skip_serializing_if = "Option::is_none")]
+    pub subject_type: Option<String>,
+    #[serde(rename = "subject-name", skip_serializing_if = "Option::is_none")]
+    pub subject_name: Option<Title>,
+    #[serde(rename = "subject-url", skip_serializing_if = "Option::is_none")]
+    pub subject_url: Option<Value<String>>,
+    #[serde(
+        rename = "convening-organization",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub convening_organization: Option<Organization>,
+    pub visibility: String,
+    pub path: String,
+}
+
+impl OrcidRecord for OrcidPeerReviewDetail {
+    fn put_code(&self) -> u64 {
+        self.put_code
+    }
+
+    fn last_modified(&self) -> u64 {
+        self.last_modified_date.value
+    }
+
+    fn title(&self) -> Option<&str> {
+        match &self.convening_organization {
+            Some(org) => Some(&org.name),
+            None => Some(&self.review_group_id),
+        }
+    }
+}
+
+impl OrcidPeerReviewDetail {
+    pub fn from_reader<R: std::io::Read>(reader: R) -> Result<Self, serde_json::Error> {
+        serde_json::from_reader(reader)
+    }
+}
+
+// Reviews of one subject grouped by ORCID (`peer-review-group` inside a group)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeerReviewDuplicates {
+    #[serde(rename = "last-modified-date", skip_serializing_if = "Option::is_none")]
+    pub last_modified_date: Option<Value<u64>>,
+    #[serde(rename = "external-ids", skip_serializing_if = "Option::is_none")]
+    pub external_ids: Option<ExternalIds>,
+    #[serde(rename = "peer-review-summary")]
+    pub peer_review_summary: Vec<OrcidPeerReviewSummary>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeerReviewGroup {
+    #[serde(rename = "last-modified-date", skip_serializing_if = "Option::is_none")]
+    pub last_modified_date: Option<Value<u64>>,
+    #[serde(rename = "external-ids", skip_serializing_if = "Option::is_none")]
+    pub external_ids: Option<ExternalIds>,
+    #[serde(rename = "peer-review-group")]
+    pub peer_review_group: Vec<PeerReviewDuplicates>,
+}
+
+// Response JSON from {BASE}/{id}/peer-reviews
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrcidPeerReviews {
+    #[serde(rename = "last-modified-date", skip_serializing_if = "Option::is_none")]
+    pub last_modified_date: Option<Value<u64>>,
+    pub group: Vec<PeerReviewGroup>,
+    pub path: String,
+}
+
+impl OrcidPeerReviews {
+    pub fn from_reader<R: std::io::Read>(reader: R) -> Result<Self, serde_json::Error> {
+        serde_json::from_reader(reader)
+    }
+
+    // Summaries of all groups and their duplicates, in response order
+    pub fn into_summaries(self) -> Vec<OrcidPeerReviewSummary> {
+        self.group
+            .into_iter()
+            .flat_map(|g| g.peer_review_group)
+            .flat_map(|d| d.peer_review_summary)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PEER_REVIEWS: &str = include_str!("../tests/fixtures/peer-reviews.json");
+    const PEER_REVIEW: &str = include_str!("../tests/fixtures/peer-review.json");
+
+    #[test]
+    fn parses_a_peer_reviews_response() {
+        let response = OrcidPeerReviews::from_reader(PEER_REVIEWS.as_bytes()).unwrap();
+        assert_eq!(response.path, "/0000-0002-1825-0097/peer-reviews");
+        assert_eq!(response.group[0].peer_review_group.len(), 2);
+
+        // Summaries of every duplicate group are listed
+        let summaries = response.into_summaries();
+        let put_codes: Vec<u64> = summaries.iter().map(OrcidRecord::put_code).collect();
+        assert_eq!(put_codes, [4001, 4002]);
+        assert_eq!(summaries[0].title(), Some("Journal of Psychoceramics"));
+        // Without a convening organization the group id names the record
+        assert_eq!(summaries[1].title(), Some("issn:0000-0001"));
+        assert_eq!(summaries[1].last_modified(), 1680000000000);
+    }
+
+    #[test]
+    fn parses_a_peer_review_detail() {
+        let detail = OrcidPeerReviewDetail::from_reader(PEER_REVIEW.as_bytes()).unwrap();
+        assert_eq!(detail.put_code(), 4001);
+        assert_eq!(detail.last_modified(), 1700000500000);
+        assert_eq!(detail.subject_type.as_deref(), Some("journal-article"));
+        assert_eq!(
+            detail
+                .subject_external_identifier
+                .as_ref()
+                .unwrap()
+                .external_id_value,
+            "10.5555/87654321"
+        );
+        assert_eq!(
+            detail.subject_name.as_ref().unwrap().title.value,
+            "On the glazing of pots"
+        );
+        assert_eq!(detail.subject_url, None);
+    }
+
+    #[test]
+    fn peer_review_records_round_trip() {
+        let detail = OrcidPeerReviewDetail::from_reader(PEER_REVIEW.as_bytes()).unwrap();
+        let json = serde_json::to_string_pretty(&[&detail]).unwrap();
+        let back: Vec<OrcidPeerReviewDetail> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, [detail]);
+        assert_eq!(serde_json::to_string_pretty(&back).unwrap(), json);
+        assert!(!json.contains("null"));
+    }
+}
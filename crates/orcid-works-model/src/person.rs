@@ -0,0 +1,355 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Source, Value};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PersonName {
+    #[serde(rename = "created-date", skip_serializing_if = "Option::is_none")]
+    pub created_date: Option<Value<u64>>,
+    #[serde(rename = "last-modified-date", skip_serializing_if = "Option::is_none")]
+    pub last_modified_date: Option<Value<u64>>,
+    #[serde(rename = "given-names", skip_serializing_if = "Option::is_none")]
+    pub given_names: Option<Value<String>>,
+    #[serde(rename = "family-name", skip_serializing_if = "Option::is_none")]
+    pub family_name: Option<Value<String>>,
+    #[serde(rename = "credit-name", skip_serializing_if = "Option::is_none")]
+    pub credit_name: Option<Value<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<Source>,
+    pub visibility: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Biography {
+    #[serde(rename = "created-date", skip_serializing_if = "Option::is_none")]
+    pub created_date: Option<Value<u64>>,
+    #[serde(rename = "last-modified-date", skip_serializing_if = "Option::is_none")]
+    pub last_modified_date: Option<Value<u64>>,
+    pub content: String,
+    pub visibility: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+// Other name or keyword: a free-text `content` entry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PersonContent {
+    #[serde(rename = "put-code")]
+    pub put_code: u64,
+    #[serde(rename = "created-date", skip_serializing_if = "Option::is_none")]
+    pub created_date: Option<Value<u64>>,
+    #[serde(rename = "last-modified-date", skip_serializing_if = "Option::is_none")]
+    pub last_modified_date: Option<Value<u64>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<Source>,
+    pub content: String,
+    pub visibility: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(rename = "display-index", skip_serializing_if = "Option::is_none")]
+    pub display_index: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OtherNames {
+    #[serde(rename = "last-modified-date", skip_serializing_if = "Option::is_none")]
+    pub last_modified_date: Option<Value<u64>>,
+    #[serde(rename = "other-name")]
+    pub other_name: Vec<PersonContent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Keywords {
+    #[serde(rename = "last-modified-date", skip_serializing_if = "Option::is_none")]
+    pub last_modified_date: Option<Value<u64>>,
+    pub keyword: Vec<PersonContent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResearcherUrl {
+    #[serde(rename = "put-code")]
+    pub put_code: u64,
+    #[serde(rename = "created-date", skip_serializing_if = "Option::is_none")]
+    pub created_date: Option<Value<u64>>,
+    #[serde(rename = "last-modified-date", skip_serializing_if = "Option::is_none")]
+    pub last_modified_date: Option<Value<u64>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<Source>,
+    #[serde(rename = "url-name", skip_serializing_if = "Option::is_none")]
+    pub url_name: Option<String>,
+    pub url: Value<String>,
+    pub visibility: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(rename = "display-index", skip_serializing_if = "Option::is_none")]
+    pub display_index: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResearcherUrls {
+    #[serde(rename = "last-modified-date", skip_serializing_if = "Option::is_none")]
+    pub last_modified_date: Option<Value<u64>>,
+    #[serde(rename = "researcher-url")]
+    pub researcher_url: Vec<ResearcherUrl>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Email {
+    #[serde(rename = "put-code", skip_serializing_if = "Option::is_none")]
+    pub put_code: Option<u64>,
+    #[serde(rename = "created-date", skip_serializing_if = "Option::is_none")]
+    pub created_date: Option<Value<u64>>,
+    #[serde(rename = "last-modified-date", skip_serializing_if = "Option::is_none")]
+    pub last_modified_date: Option<Value<u64>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<Source>,
+    pub email: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    pub visibility: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verified: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub primary: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Emails {
+    #[serde(rename = "last-modified-date", skip_serializing_if = "Option::is_none")]
+    pub last_modified_date: Option<Value<u64>>,
+    pub email: Vec<Email>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Address {
+    #[serde(rename = "put-code")]
+    pub put_code: u64,
+    #[serde(rename = "created-date", skip_serializing_if = "Option::is_none")]
+    pub created_date: Option<Value<u64>>,
+    #[serde(rename = "last-modified-date", skip_serializing_if = "Option::is_none")]
+    pub last_modified_date: Option<Value<u64>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<Source>,
+    pub country: Value<String>,
+    pub visibility: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(rename = "display-index", skip_serializing_if = "Option::is_none")]
+    pub display_index: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Addresses {
+    #[serde(rename = "last-modified-date", skip_serializing_if = "Option::is_none")]
+    pub last_modified_date: Option<Value<u64>>,
+    pub address: Vec<Address>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+// Person-level identifier (Scopus Author ID, ResearcherID, ...)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PersonExternalIdentifier {
+    #[serde(rename = "put-code")]
+    pub put_code: u64,
+    #[serde(rename = "created-date", skip_serializing_if = "Option::is_none")]
+    pub created_date: Option<Value<u64>>,
+    #[serde(rename = "last-modified-date", skip_serializing_if = "Option::is_none")]
+    pub last_modified_date: Option<Value<u64>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<Source>,
+    #[serde(rename = "external-id-type")]
+    pub external_id_type: String,
+    #[serde(rename = "external-id-value")]
+    pub external_id_value: String,
+    #[serde(rename = "external-id-url", skip_serializing_if = "Option::is_none")]
+    pub external_id_url: Option<Value<String>>,
+    #[serde(rename = "external-id-relationship")]
+    pub external_id_relationship: String,
+    pub visibility: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(rename = "display-index", skip_serializing_if = "Option::is_none")]
+    pub display_index: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PersonExternalIdentifiers {
+    #[serde(rename = "last-modified-date", skip_serializing_if = "Option::is_none")]
+    pub last_modified_date: Option<Value<u64>>,
+    #[serde(rename = "external-identifier")]
+    pub external_identifier: Vec<PersonExternalIdentifier>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+// Response JSON from {BASE}/{id}/person
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrcidPerson {
+    #[serde(rename = "last-modified-date", skip_serializing_if = "Option::is_none")]
+    pub last_modified_date: Option<Value<u64>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<PersonName>,
+    #[serde(rename = "other-names", skip_serializing_if = "Option::is_none")]
+    pub other_names: Option<OtherNames>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub biography: Option<Biography>,
+    #[serde(rename = "researcher-urls", skip_serializing_if = "Option::is_none")]
+    pub researcher_urls: Option<ResearcherUrls>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub emails: Option<Emails>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub addresses: Option<Addresses>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keywords: Option<Keywords>,
+    #[serde(
+        rename = "external-identifiers",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub external_identifiers: Option<PersonExternalIdentifiers>,
+    pub path: String,
+}
+
+impl OrcidPerson {
+    pub fn from_reader<R: std::io::Read>(reader: R) -> Result<Self, serde_json::Error> {
+        serde_json::from_reader(reader)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PERSON: &str = include_str!("../tests/fixtures/person.json");
+
+    fn fixture_with(edit: impl FnOnce(&mut serde_json::Map<String, serde_json::Value>)) -> String {
+        let mut value: serde_json::Value = serde_json::from_str(PERSON).unwrap();
+        edit(value.as_object_mut().unwrap());
+        value.to_string()
+    }
+
+    #[test]
+    fn parses_a_person_response() {
+        let person = OrcidPerson::from_reader(PERSON.as_bytes()).unwrap();
+        assert_eq!(person.path, "/0000-0002-1825-0097/person");
+        assert_eq!(person.last_modified_date.unwrap().value, 1700000500000);
+
+        let name = person.name.as_ref().unwrap();
+        assert_eq!(name.given_names.as_ref().unwrap().value, "Josiah");
+        assert_eq!(name.family_name.as_ref().unwrap().value, "Carberry");
+        assert_eq!(
+            name.credit_name.as_ref().unwrap().value,
+            "Josiah S. Carberry"
+        );
+        assert_eq!(name.source, None);
+        assert_eq!(name.visibility, "public");
+
+        let biography = person.biography.as_ref().unwrap();
+        assert!(
+            biography
+                .content
+                .starts_with("Josiah Carberry is a fictitious")
+        );
+
+        let other_names = &person.other_names.as_ref().unwrap().other_name;
+        assert_eq!(other_names[0].content, "J. S. Carberry");
+        let keywords: Vec<&str> = person
+            .keywords
+            .as_ref()
+            .unwrap()
+            .keyword
+            .iter()
+            .map(|k| k.content.as_str())
+            .collect();
+        assert_eq!(keywords, ["psychoceramics", "ceramic fracture"]);
+        let urls = &person.researcher_urls.as_ref().unwrap().researcher_url;
+        assert_eq!(urls[0].url.value, "https://example.edu/~jcarberry");
+        assert_eq!(urls[0].url_name.as_deref(), Some("Brown University page"));
+        assert!(person.emails.as_ref().unwrap().email.is_empty());
+        assert_eq!(
+            person.addresses.as_ref().unwrap().address[0].country.value,
+            "US"
+        );
+
+        let ids = &person
+            .external_identifiers
+            .as_ref()
+            .unwrap()
+            .external_identifier;
+        assert_eq!(
+            (
+                ids[0].external_id_type.as_str(),
+                ids[0].external_id_value.as_str()
+            ),
+            ("Scopus Author ID", "7007156898")
+        );
+        assert_eq!(
+            ids[0]
+                .source
+                .as_ref()
+                .unwrap()
+                .source_client_id
+                .as_ref()
+                .unwrap()
+                .path
+                .as_deref(),
+            Some("0000-0002-5982-8983")
+        );
+    }
+
+    #[test]
+    fn name_and_biography_may_be_null_or_missing() {
+        // A private name or biography is sent as `null`
+        let json = fixture_with(|person| {
+            person.insert("name".to_string(), serde_json::Value::Null);
+            person.insert("biography".to_string(), serde_json::Value::Null);
+        });
+        let person = OrcidPerson::from_reader(json.as_bytes()).unwrap();
+        assert_eq!((person.name, person.biography), (None, None));
+        assert_eq!(person.keywords.unwrap().keyword.len(), 2);
+
+        let json = fixture_with(|person| {
+            person.remove("name");
+            person.remove("biography");
+        });
+        let person = OrcidPerson::from_reader(json.as_bytes()).unwrap();
+        assert_eq!((person.name, person.biography), (None, None));
+
+        // Nothing public but the record itself
+        let person = OrcidPerson::from_reader(
+            r#"{"name": null, "biography": null, "path": "/0000-0002-1825-0097/person"}"#
+                .as_bytes(),
+        )
+        .unwrap();
+        assert_eq!(person.path, "/0000-0002-1825-0097/person");
+        assert!(person.name.is_none() && person.other_names.is_none() && person.emails.is_none());
+    }
+
+    #[test]
+    fn person_round_trips() {
+        for json in [
+            PERSON.to_string(),
+            fixture_with(|person| {
+                person.insert("name".to_string(), serde_json::Value::Null);
+                person.remove("biography");
+            }),
+        ] {
+            let person = OrcidPerson::from_reader(json.as_bytes()).unwrap();
+            let written = serde_json::to_string_pretty(&person).unwrap();
+            let back = OrcidPerson::from_reader(written.as_bytes()).unwrap();
+            assert_eq!(back, person);
+            assert_eq!(serde_json::to_string_pretty(&back).unwrap(), written);
+            assert!(!written.contains("null"));
+        }
+    }
+}